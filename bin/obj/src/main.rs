@@ -2,13 +2,16 @@ use anyhow::Result;
 use geometry::transform::Transform;
 use geometry::Point3f;
 use gfx::camera::Camera;
+use gfx::cpu::canvas::PixelFormat;
+use gfx::cpu::{CullMode, WrapMode, ZBuffer};
+use gfx::light::Light;
 use loader::obj::Obj;
 use loader::png::Png;
 use math::Vec3f;
 use sdl2::event::Event;
 use sdl2::keyboard::Keycode;
 use sdl2::pixels::{Color, PixelFormatEnum};
-use sdl2::render::Canvas;
+use sdl2::render::{Canvas, Texture};
 use sdl2::video::Window;
 use sdl2::TimerSubsystem;
 use std::f32::consts::FRAC_PI_2;
@@ -16,6 +19,9 @@ use std::f32::consts::FRAC_PI_2;
 pub struct Game {
     event_pump: sdl2::EventPump,
     canvas: Canvas<Window>,
+    // Render target of the software renderer, reused between frames
+    frame: Texture,
+    z_buffer: ZBuffer,
     camera: Camera,
     timer: TimerSubsystem,
     ticks: u32,
@@ -35,7 +41,9 @@ impl Game {
         let timer = sdl_context.timer().expect("failed to get timer subsystem");
 
         let obj = Obj::from_file("assets/head.obj")?;
-        let texture = Png::from_file("assets/head_diffuse.png")?;
+        let mut texture = Png::from_file("assets/head_diffuse.png")?;
+        // OBJ texture coordinates have their origin at the bottom left corner
+        texture.flip_vertical();
         // We create a window.
         let window = video_subsystem
             .window("sdl2 demo", WIDTH, HEIGHT)
@@ -45,17 +53,27 @@ impl Game {
             .into_canvas()
             .build()
             .expect("failed to build window's canvas");
+        let frame = canvas.texture_creator().create_texture_streaming(
+            PixelFormatEnum::RGB24,
+            WIDTH,
+            HEIGHT,
+        )?;
         let mut camera = Camera {
             xform: Transform::translation(Vec3f::new(-10.0, 0.0, 0.0)),
             projection: Transform::infinite_projection(1.0, 1.0, 0.1, 0.001),
+            reversed_z: false,
         };
         camera.look_at(Point3f::new(0., 0., 0.));
+        let viewport = canvas.viewport();
+        let z_buffer = ZBuffer::new(viewport.width(), viewport.height(), camera.reversed_z);
 
         let event_pump = sdl_context.event_pump().unwrap();
 
         Ok(Self {
             event_pump,
             canvas,
+            frame,
+            z_buffer,
             camera,
             timer,
             ticks: 0,
@@ -121,32 +139,45 @@ impl emscripten_main_loop::MainLoop for Game {
         let object_transform = Transform::rotation_z(self.angle) * Transform::rotation_x(FRAC_PI_2);
         let view = self.camera.view().unwrap() * object_transform;
 
-        let texture_creator = self.canvas.texture_creator();
-        let mut texture = texture_creator
-            .create_texture_streaming(PixelFormatEnum::RGB24, WIDTH, HEIGHT)
-            .expect("Failed to create texture");
-
-        texture
+        self.z_buffer.clear();
+        let z_buffer = &mut self.z_buffer;
+        let viewport = self.canvas.viewport();
+        let obj = &self.obj;
+        let texture = &self.texture;
+        let projection = self.camera.projection;
+        // From above, slightly from the side
+        let light = Light {
+            direction: Vec3f::new(1.0, 1.0, -2.0),
+            color: gfx::color::Color::rgb(255, 255, 255),
+            ambient: 0.1,
+        };
+        self.frame
             .with_lock(None, |buffer: &mut [u8], _pitch: usize| {
-                let viewport = self.canvas.viewport();
+                // The texture keeps the previous frame
+                buffer.fill(0);
                 let mut canvas = gfx::cpu::canvas::Canvas {
                     buffer,
                     width: viewport.width(),
                     height: viewport.height(),
+                    format: PixelFormat::RGB24,
                 };
                 gfx::cpu::draw_obj(
                     &mut canvas,
-                    &self.obj,
-                    &self.texture,
+                    obj,
+                    texture,
+                    WrapMode::Clamp,
+                    &light,
                     view,
-                    self.camera.projection,
+                    projection,
+                    z_buffer,
+                    CullMode::Back,
                 );
             })
             .expect("Failed to render on texture");
 
         self.canvas.clear();
         self.canvas
-            .copy(&texture, None, None)
+            .copy(&self.frame, None, None)
             .expect("Failed to copy texture to canvas");
         self.canvas.present();
 
@@ -19,6 +19,7 @@ fn pixel_format(image: &Png) -> Result<PixelFormatEnum> {
     match (&image.bit_depth, &image.color_type) {
         (Bits8, RGB) => Ok(PixelFormatEnum::RGB24),
         (Bits8, RGBA) => Ok(PixelFormatEnum::RGBA32),
+        (Bits16, ct) => bail!("16-bit images aren't supported ({:?})", ct),
         (bpp, ct) => bail!("Can't handle these: ({:?}, {:?}", bpp, ct),
     }
 }
@@ -29,6 +30,12 @@ impl Game {
         let video_subsystem = sdl_context.video().expect("failed to get video context");
 
         let img = Png::from_file("assets/PNG_Test_SH.png")?;
+        // SDL has no 8-bit grayscale texture formats, expand to RGB24 or RGBA32
+        let img = match (img.bit_depth, img.color_type) {
+            (png::BitDepth::Bits8, png::ColorType::Grayscale) => img.to_rgb8()?,
+            (png::BitDepth::Bits8, png::ColorType::GrayscaleAlpha) => img.to_rgba8()?,
+            _ => img,
+        };
         println!("{}", img.bytes_per_pixel);
         // We create a window.
         let window = video_subsystem
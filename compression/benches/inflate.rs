@@ -0,0 +1,39 @@
+//! Decompression throughput, run with `cargo bench -p compression`.
+
+use compression::zlib;
+use std::time::{Duration, Instant};
+
+// 64 KiB of bytes k with probability proportional to 0.9^k, compressed with the reference zlib
+// implementation at level 9. The skewed distribution gives dynamic Huffman blocks with literal
+// codes of up to 15 bits.
+const LITERALS: &[u8] = include_bytes!("../testdata/literals.zz");
+const LITERALS_LEN: usize = 65536;
+
+// Three copies of 31000 pseudorandom bases, mostly back-references near the 32 KiB limit
+const WINDOW: &[u8] = include_bytes!("../testdata/window.zz");
+const WINDOW_LEN: usize = 93000;
+
+fn bench(name: &str, compressed: &[u8], len: usize) {
+    let mut out = Vec::with_capacity(len);
+    let mut iterations = 0;
+    let start = Instant::now();
+    while start.elapsed() < Duration::from_secs(2) {
+        out.clear();
+        zlib::decompress(compressed, &mut out).unwrap();
+        assert_eq!(out.len(), len);
+        iterations += 1;
+    }
+    let elapsed = start.elapsed();
+    let mib_per_s = (iterations * len) as f64 / elapsed.as_secs_f64() / (1024.0 * 1024.0);
+    println!(
+        "{:<10} {:>8.1} µs/iter {:>8.1} MiB/s",
+        name,
+        elapsed.as_secs_f64() * 1e6 / iterations as f64,
+        mib_per_s
+    );
+}
+
+fn main() {
+    bench("literals", LITERALS, LITERALS_LEN);
+    bench("window", WINDOW, WINDOW_LEN);
+}
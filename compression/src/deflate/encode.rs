@@ -0,0 +1,368 @@
+use super::huffman::{
+    BASE_DISTANCE, BASE_LENGTH, DISTANCE_EXTRA_BITS, LENGTH_EXTRA_BITS, STATIC_DISTANCE_ALPHABET,
+    STATIC_LITERAL_ALPHABET,
+};
+use super::{DeflateError, WINDOW_SIZE};
+use fiddling::BitOrder::{LsbFirst, MsbFirst};
+use fiddling::BitWriter;
+use lazy_static::lazy_static;
+use std::io::Write;
+
+const MIN_MATCH: usize = 3;
+const MAX_MATCH: usize = 258;
+
+// How many earlier positions with the same hash are tried when looking for a match
+const MAX_CHAIN_LENGTH: usize = 128;
+
+// Input bytes per fixed Huffman block
+const FIXED_BLOCK_SIZE: usize = 1 << 16;
+
+const HASH_BITS: usize = 15;
+
+lazy_static! {
+    // (code, code length) indexed by symbol
+    static ref STATIC_LITERAL_CODES: Vec<(u16, u8)> = (0u16..288)
+        .map(|symbol| STATIC_LITERAL_ALPHABET.code(symbol).unwrap())
+        .collect();
+    static ref STATIC_DISTANCE_CODES: Vec<(u16, u8)> = (0u16..30)
+        .map(|symbol| STATIC_DISTANCE_ALPHABET.code(symbol).unwrap())
+        .collect();
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Token {
+    Literal(u8),
+    Match { length: u16, distance: u16 },
+}
+
+/// A greedy LZ77 match finder keeping hash chains over the last `WINDOW_SIZE` positions
+struct MatchFinder<'a> {
+    input: &'a [u8],
+    // Most recent position for each hash, plus one so that zero means none
+    head: Vec<usize>,
+    // Previous position with the same hash, plus one, indexed by position modulo WINDOW_SIZE
+    prev: Vec<usize>,
+}
+
+impl<'a> MatchFinder<'a> {
+    fn new(input: &'a [u8]) -> Self {
+        MatchFinder {
+            input,
+            head: vec![0; 1 << HASH_BITS],
+            prev: vec![0; WINDOW_SIZE],
+        }
+    }
+
+    fn hash(&self, pos: usize) -> usize {
+        let bytes = &self.input[pos..pos + MIN_MATCH];
+        let h = (bytes[0] as usize) << 10 ^ (bytes[1] as usize) << 5 ^ bytes[2] as usize;
+        h & ((1 << HASH_BITS) - 1)
+    }
+
+    fn insert(&mut self, pos: usize) {
+        if pos + MIN_MATCH <= self.input.len() {
+            let h = self.hash(pos);
+            self.prev[pos % WINDOW_SIZE] = self.head[h];
+            self.head[h] = pos + 1;
+        }
+    }
+
+    /// The longest match for the bytes at `pos` as (length, distance), if any
+    fn find_match(&self, pos: usize) -> Option<(usize, usize)> {
+        if pos + MIN_MATCH > self.input.len() {
+            return None;
+        }
+        let max_length = (self.input.len() - pos).min(MAX_MATCH);
+        let mut best: Option<(usize, usize)> = None;
+        let mut candidate = self.head[self.hash(pos)];
+        for _ in 0..MAX_CHAIN_LENGTH {
+            if candidate == 0 || pos - (candidate - 1) > WINDOW_SIZE {
+                break;
+            }
+            let start = candidate - 1;
+            let length = self.input[start..]
+                .iter()
+                .zip(&self.input[pos..pos + max_length])
+                .take_while(|(a, b)| a == b)
+                .count();
+            let best_length = best.map_or(MIN_MATCH - 1, |(best_length, _)| best_length);
+            if length > best_length {
+                best = Some((length, pos - start));
+                if length == max_length {
+                    break;
+                }
+            }
+            let next = self.prev[start % WINDOW_SIZE];
+            // Positions only ever get older along the chain, anything else was overwritten
+            if next >= candidate {
+                break;
+            }
+            candidate = next;
+        }
+        best
+    }
+}
+
+fn tokenize(input: &[u8]) -> Vec<Token> {
+    let mut finder = MatchFinder::new(input);
+    let mut tokens = Vec::new();
+    let mut pos = 0;
+    while pos < input.len() {
+        match finder.find_match(pos) {
+            Some((length, distance)) => {
+                tokens.push(Token::Match {
+                    length: length as u16,
+                    distance: distance as u16,
+                });
+                for p in pos..pos + length {
+                    finder.insert(p);
+                }
+                pos += length;
+            }
+            None => {
+                tokens.push(Token::Literal(input[pos]));
+                finder.insert(pos);
+                pos += 1;
+            }
+        }
+    }
+    tokens
+}
+
+// Index of the last base value not greater than `value`
+fn base_index(bases: &[u16], value: u16) -> usize {
+    bases.iter().rposition(|&base| base <= value).unwrap()
+}
+
+fn write_code<W: Write>(bits: &mut BitWriter<W>, (code, length): (u16, u8)) -> std::io::Result<()> {
+    bits.write_bits(code as u64, length as usize, MsbFirst)
+}
+
+fn write_token<W: Write>(bits: &mut BitWriter<W>, token: Token) -> std::io::Result<()> {
+    match token {
+        Token::Literal(value) => write_code(bits, STATIC_LITERAL_CODES[value as usize]),
+        Token::Match { length, distance } => {
+            let length_idx = base_index(&BASE_LENGTH, length);
+            write_code(bits, STATIC_LITERAL_CODES[257 + length_idx])?;
+            bits.write_bits(
+                (length - BASE_LENGTH[length_idx]) as u64,
+                LENGTH_EXTRA_BITS[length_idx],
+                LsbFirst,
+            )?;
+
+            let distance_idx = base_index(&BASE_DISTANCE, distance);
+            write_code(bits, STATIC_DISTANCE_CODES[distance_idx])?;
+            bits.write_bits(
+                (distance - BASE_DISTANCE[distance_idx]) as u64,
+                DISTANCE_EXTRA_BITS[distance_idx],
+                LsbFirst,
+            )
+        }
+    }
+}
+
+fn token_len(token: &Token) -> usize {
+    match token {
+        Token::Literal(_) => 1,
+        Token::Match { length, .. } => *length as usize,
+    }
+}
+
+/// Compress `input` as fixed Huffman deflate blocks, appending them to `out`.
+///
+/// # Examples
+///
+/// ```rust
+/// use compression::deflate::{compress_fixed, decompress_blocks};
+///
+/// let input = b"Hello, Hello, Hello, Hello!".to_vec();
+/// let mut compressed = Vec::new();
+/// compress_fixed(&input, &mut compressed).unwrap();
+/// assert!(compressed.len() < input.len());
+///
+/// let mut decompressed = Vec::new();
+/// decompress_blocks(&compressed, &mut decompressed).unwrap();
+/// assert_eq!(decompressed, input);
+/// ```
+pub fn compress_fixed(input: &[u8], out: &mut Vec<u8>) -> Result<(), DeflateError> {
+    let tokens = tokenize(input);
+    let mut bits = BitWriter::new(out);
+
+    // Split the tokens into blocks of roughly FIXED_BLOCK_SIZE input bytes, always writing at
+    // least one (final) block even for empty input
+    let mut blocks: Vec<&[Token]> = Vec::new();
+    let mut block_start = 0;
+    let mut block_bytes = 0;
+    for (i, token) in tokens.iter().enumerate() {
+        block_bytes += token_len(token);
+        if block_bytes >= FIXED_BLOCK_SIZE {
+            blocks.push(&tokens[block_start..=i]);
+            block_start = i + 1;
+            block_bytes = 0;
+        }
+    }
+    if block_start < tokens.len() || blocks.is_empty() {
+        blocks.push(&tokens[block_start..]);
+    }
+
+    let n_blocks = blocks.len();
+    for (i, block) in blocks.into_iter().enumerate() {
+        let is_final = i + 1 == n_blocks;
+        // BFINAL, then BTYPE = 0b01
+        bits.write_bits(is_final as u64, 1, LsbFirst)?;
+        bits.write_bits(0b01, 2, LsbFirst)?;
+        for &token in block {
+            write_token(&mut bits, token)?;
+        }
+        write_code(&mut bits, STATIC_LITERAL_CODES[256])?;
+    }
+    bits.flush()?;
+    Ok(())
+}
+
+// The most bytes a stored block can hold, as LEN is 16 bits
+const MAX_STORED_BLOCK_SIZE: usize = 65535;
+
+/// Store `input` uncompressed as deflate blocks, appending them to `out`. Useful for data that
+/// doesn't compress, as the output is only five bytes per 65535 input bytes larger than `input`.
+///
+/// # Examples
+///
+/// ```rust
+/// use compression::deflate::{compress_stored, decompress_blocks};
+///
+/// let input = b"Hello, stored block!".to_vec();
+/// let mut compressed = Vec::new();
+/// compress_stored(&input, &mut compressed);
+/// assert_eq!(compressed.len(), input.len() + 5);
+///
+/// let mut decompressed = Vec::new();
+/// decompress_blocks(&compressed, &mut decompressed).unwrap();
+/// assert_eq!(decompressed, input);
+/// ```
+pub fn compress_stored(input: &[u8], out: &mut Vec<u8>) {
+    let mut chunks = input.chunks(MAX_STORED_BLOCK_SIZE).peekable();
+    if chunks.peek().is_none() {
+        write_stored_block(&[], true, out);
+    }
+    while let Some(chunk) = chunks.next() {
+        write_stored_block(chunk, chunks.peek().is_none(), out);
+    }
+}
+
+fn write_stored_block(bytes: &[u8], is_final: bool, out: &mut Vec<u8>) {
+    // BFINAL, BTYPE = 0b00 and padding to the byte boundary
+    out.push(is_final as u8);
+    let len = bytes.len() as u16;
+    out.extend_from_slice(&len.to_le_bytes());
+    out.extend_from_slice(&(!len).to_le_bytes());
+    out.extend_from_slice(bytes);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::deflate::decompress_blocks;
+
+    fn assert_round_trip(input: &[u8]) -> Vec<u8> {
+        let mut compressed = Vec::new();
+        compress_fixed(input, &mut compressed).unwrap();
+        let mut decompressed = Vec::new();
+        decompress_blocks(&compressed, &mut decompressed).unwrap();
+        assert_eq!(decompressed.len(), input.len());
+        assert!(decompressed == input);
+        compressed
+    }
+
+    #[test]
+    fn test_compress_fixed_round_trip() {
+        assert_round_trip(b"");
+        assert_round_trip(b"a");
+        assert_round_trip(b"aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa");
+        assert_round_trip(&(0..=255).collect::<Vec<u8>>());
+
+        // Pseudo-random bytes don't compress, but must still round trip
+        let random: Vec<u8> = (0u32..100_000)
+            .map(|i| (i.wrapping_mul(2654435761) >> 13) as u8)
+            .collect();
+        assert_round_trip(&random);
+    }
+
+    #[test]
+    fn test_compress_fixed_long_repeats() {
+        // Repeats at every distance code and with lengths up to MAX_MATCH, spanning several blocks
+        let mut input = Vec::new();
+        let mut seed = 1u32;
+        while input.len() < 3 * FIXED_BLOCK_SIZE {
+            seed = seed.wrapping_mul(1103515245).wrapping_add(12345);
+            let distance = 1 + (seed >> 8) as usize % input.len().clamp(1, WINDOW_SIZE);
+            let length = 3 + (seed >> 4) as usize % 300;
+            if distance <= input.len() {
+                for _ in 0..length {
+                    input.push(input[input.len() - distance]);
+                }
+            }
+            input.push((seed >> 24) as u8);
+        }
+        let compressed = assert_round_trip(&input);
+        assert!(compressed.len() < input.len() / 2);
+
+        let long_run = vec![7u8; 5000];
+        let compressed = assert_round_trip(&long_run);
+        assert!(compressed.len() < 100);
+    }
+
+    #[test]
+    fn test_tokenize_distances() {
+        let tokens = tokenize(b"abcabcabcx");
+        assert_eq!(
+            tokens,
+            vec![
+                Token::Literal(b'a'),
+                Token::Literal(b'b'),
+                Token::Literal(b'c'),
+                Token::Match {
+                    length: 6,
+                    distance: 3
+                },
+                Token::Literal(b'x'),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_compress_stored() {
+        for &len in &[
+            0,
+            1,
+            MAX_STORED_BLOCK_SIZE,
+            MAX_STORED_BLOCK_SIZE + 1,
+            200_000,
+        ] {
+            let input: Vec<u8> = (0..len as u32)
+                .map(|i| (i.wrapping_mul(2654435761) >> 13) as u8)
+                .collect();
+            let mut compressed = Vec::new();
+            compress_stored(&input, &mut compressed);
+
+            let n_blocks = len.div_ceil(MAX_STORED_BLOCK_SIZE).max(1);
+            assert_eq!(compressed.len(), len + 5 * n_blocks);
+
+            // Walk the block headers
+            let mut pos = 0;
+            for block in 0..n_blocks {
+                assert_eq!(compressed[pos], (block + 1 == n_blocks) as u8);
+                let block_len = u16::from_le_bytes([compressed[pos + 1], compressed[pos + 2]]);
+                let block_nlen = u16::from_le_bytes([compressed[pos + 3], compressed[pos + 4]]);
+                assert_eq!(block_len & block_nlen, 0);
+                assert_eq!(block_len, !block_nlen);
+                pos += 5 + block_len as usize;
+            }
+            assert_eq!(pos, compressed.len());
+
+            let mut decompressed = Vec::new();
+            decompress_blocks(&compressed, &mut decompressed).unwrap();
+            assert!(decompressed == input, "{} bytes", len);
+        }
+    }
+}
@@ -1,9 +1,9 @@
-use anyhow::{bail, Error, Result};
+use super::DeflateError;
 use fiddling::BitOrder::{LsbFirst, MsbFirst};
 use fiddling::BitStream;
 use lazy_static::lazy_static;
 use std::io::Read;
-use std::iter;
+use std::{cmp, io, iter};
 
 const CODE_LENGTH_ALPHABET_INDICES: [usize; 19] = [
     16, 17, 18, 0, 8, 7, 9, 6, 10, 5, 11, 4, 12, 3, 13, 2, 14, 1, 15,
@@ -16,10 +16,25 @@ struct SymbolEntry<S: Copy + Ord> {
     code: u16,
 }
 
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum LutEntry {
+    Empty,
+    // Index into `symbol_entries`
+    Symbol(usize),
+    // Codes longer than the root table's bits continue in a sub-table starting at `offset`
+    SubTable { offset: usize, bits: u8 },
+}
+
+// Alphabets with codes longer than this get a two-level lookup table, which keeps e.g. a
+// literal/length alphabet with a few 15-bit codes from allocating 32768 entries.
+const MAX_ROOT_LUT_BITS: u8 = 9;
+
 #[derive(Clone, Debug, PartialEq)]
 pub struct HuffmanAlphabet<S: Copy + Ord> {
     symbol_entries: Vec<SymbolEntry<S>>,
-    lut: Vec<Option<usize>>,
+    // Root table of `2^root_lut_bits` entries followed by the sub-tables
+    lut: Vec<LutEntry>,
+    root_lut_bits: u8,
     max_lut_code: u16,
     max_code_length: u8,
 }
@@ -27,7 +42,7 @@ pub struct HuffmanAlphabet<S: Copy + Ord> {
 lazy_static! {
     pub static ref STATIC_DISTANCE_ALPHABET: HuffmanAlphabet<u16> = {
         let code_lengths: Vec<(u16, u8)> = (0u16..32).zip(iter::repeat(5u8)).collect();
-        HuffmanAlphabet::from_code_lengths(&code_lengths[..])
+        HuffmanAlphabet::from_code_lengths(&code_lengths[..]).unwrap()
     };
     pub static ref STATIC_LITERAL_ALPHABET: HuffmanAlphabet<u16> = {
         let code_lengths: Vec<(u16, u8)> = (0u16..144)
@@ -36,43 +51,128 @@ lazy_static! {
             .chain((256..280).zip(iter::repeat(7)))
             .chain((280..288).zip(iter::repeat(8)))
             .collect();
-        HuffmanAlphabet::from_code_lengths(&code_lengths[..])
+        HuffmanAlphabet::from_code_lengths(&code_lengths[..]).unwrap()
     };
 }
 
 impl<'a, S: 'a + Copy + Ord> HuffmanAlphabet<S> {
-    pub fn from_code_lengths(code_lengths: &[(S, u8)]) -> HuffmanAlphabet<S> {
+    /// Build the canonical Huffman code for `code_lengths`, failing with
+    /// `DeflateError::OverSubscribedCodeLengths` if the lengths don't fit in a prefix code, or
+    /// `DeflateError::CodeLengthTooLong` for lengths over 15
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use compression::deflate::{DeflateError, HuffmanAlphabet};
+    ///
+    /// // Three 1-bit codes
+    /// let code_lengths = [('A', 1u8), ('B', 1), ('C', 1)];
+    /// assert!(matches!(
+    ///     HuffmanAlphabet::from_code_lengths(&code_lengths[..]),
+    ///     Err(DeflateError::OverSubscribedCodeLengths)
+    /// ));
+    /// ```
+    pub fn from_code_lengths(code_lengths: &[(S, u8)]) -> Result<HuffmanAlphabet<S>, DeflateError> {
+        Self::with_root_lut_bits(code_lengths, MAX_ROOT_LUT_BITS)
+    }
+
+    fn with_root_lut_bits(
+        code_lengths: &[(S, u8)],
+        max_root_lut_bits: u8,
+    ) -> Result<HuffmanAlphabet<S>, DeflateError> {
         let max_code_length = *code_lengths
             .iter()
             .filter(|&(_, length)| *length > 0)
             .map(|(_, len)| len)
             .max()
-            .unwrap();
-        assert!(max_code_length < 16);
+            .unwrap_or(&0);
+        if max_code_length > 15 {
+            return Err(DeflateError::CodeLengthTooLong(max_code_length));
+        }
         let non_zero_code_lengths: Vec<(S, u8)> = code_lengths
             .iter()
             .filter(|&(_, length)| *length > 0)
             .cloned()
             .collect();
-        let symbol_entries = Self::assign_codes(&non_zero_code_lengths, max_code_length);
+        let symbol_entries = Self::assign_codes(&non_zero_code_lengths, max_code_length)?;
 
         // Build lookup table
-        let mut lut: Vec<Option<usize>> = vec![None; 2usize.pow(max_code_length as u32)];
+        let root_lut_bits = cmp::min(max_code_length, max_root_lut_bits);
+        let mut lut: Vec<LutEntry> = vec![LutEntry::Empty; 1 << root_lut_bits];
+
+        // Size the sub-tables by the longest code sharing each root prefix
+        for symbol_entry in &symbol_entries {
+            if symbol_entry.length > root_lut_bits {
+                let extra_bits = symbol_entry.length - root_lut_bits;
+                let root_idx = (symbol_entry.code >> extra_bits) as usize;
+                lut[root_idx] = match lut[root_idx] {
+                    LutEntry::SubTable { bits, .. } if bits >= extra_bits => lut[root_idx],
+                    _ => LutEntry::SubTable {
+                        offset: 0,
+                        bits: extra_bits,
+                    },
+                }
+            }
+        }
+        for root_idx in 0..lut.len() {
+            if let LutEntry::SubTable { bits, .. } = lut[root_idx] {
+                let offset = lut.len();
+                lut[root_idx] = LutEntry::SubTable { offset, bits };
+                lut.resize(offset + (1 << bits), LutEntry::Empty);
+            }
+        }
 
         for (tree_idx, symbol_entry) in symbol_entries.iter().enumerate() {
-            let shift_by = max_code_length - symbol_entry.length;
-            let lut_segment_start = (symbol_entry.code << shift_by) as usize;
-            let lut_segment_end = ((symbol_entry.code + 1) << shift_by) as usize;
+            // Fill the entries of the table the code ends up in, with the code's bits remaining
+            // after the root table's prefix (if any)
+            let (table_start, table_bits, code, code_length) =
+                if symbol_entry.length > root_lut_bits {
+                    let extra_bits = symbol_entry.length - root_lut_bits;
+                    let root_idx = (symbol_entry.code >> extra_bits) as usize;
+                    match lut[root_idx] {
+                        LutEntry::SubTable { offset, bits } => (
+                            offset,
+                            bits,
+                            symbol_entry.code & ((1 << extra_bits) - 1),
+                            extra_bits,
+                        ),
+                        _ => unreachable!(),
+                    }
+                } else {
+                    (0, root_lut_bits, symbol_entry.code, symbol_entry.length)
+                };
+            let shift_by = table_bits - code_length;
+            let lut_segment_start = table_start + ((code as usize) << shift_by);
+            let lut_segment_end = table_start + (((code as usize) + 1) << shift_by);
             for lut_entry in lut.iter_mut().take(lut_segment_end).skip(lut_segment_start) {
-                *lut_entry = Some(tree_idx);
+                *lut_entry = LutEntry::Symbol(tree_idx);
             }
         }
 
-        Self {
+        Ok(Self {
             symbol_entries,
             lut,
+            root_lut_bits,
             max_lut_code: (1 << max_code_length) - 1,
             max_code_length,
+        })
+    }
+
+    // Find the symbol entry matching the `max_code_length` bit long, MSB first `code`
+    fn lookup_entry(&self, code: u16) -> Option<&SymbolEntry<S>> {
+        assert!(code <= self.max_lut_code);
+        let root_idx = (code >> (self.max_code_length - self.root_lut_bits)) as usize;
+        let entry = match self.lut[root_idx] {
+            LutEntry::SubTable { offset, bits } => {
+                let shift_by = self.max_code_length - self.root_lut_bits - bits;
+                let sub_idx = ((code >> shift_by) & ((1 << bits) - 1)) as usize;
+                self.lut[offset + sub_idx]
+            }
+            entry => entry,
+        };
+        match entry {
+            LutEntry::Symbol(tree_idx) => Some(&self.symbol_entries[tree_idx]),
+            _ => None,
         }
     }
 
@@ -93,7 +193,7 @@ impl<'a, S: 'a + Copy + Ord> HuffmanAlphabet<S> {
     /// // H       4       1111
     /// let code_lengths = [('A', 3u8), ('B', 3), ('C', 3), ('D', 3), ('E', 3), ('F', 2), ('G', 4), ('H', 4)];
     ///
-    /// let alphabet = HuffmanAlphabet::from_code_lengths(&code_lengths[..]);
+    /// let alphabet = HuffmanAlphabet::from_code_lengths(&code_lengths[..]).unwrap();
     /// assert_eq!(alphabet.lookup(0b0000).unwrap(), 'F');
     /// assert_eq!(alphabet.lookup(0b0001).unwrap(), 'F');
     /// assert_eq!(alphabet.lookup(0b0010).unwrap(), 'F');
@@ -102,11 +202,7 @@ impl<'a, S: 'a + Copy + Ord> HuffmanAlphabet<S> {
     /// assert_eq!(alphabet.lookup(0b1111).unwrap(), 'H');
     /// ```
     pub fn lookup(&self, code: u16) -> Option<S> {
-        assert!(code <= self.max_lut_code);
-        match self.lut[code as usize] {
-            None => None,
-            Some(tree_idx) => Some(self.symbol_entries[tree_idx].symbol),
-        }
+        self.lookup_entry(code).map(|entry| entry.symbol)
     }
 
     /// # Examples
@@ -127,7 +223,7 @@ impl<'a, S: 'a + Copy + Ord> HuffmanAlphabet<S> {
     /// // H       4       1111
     /// let code_lengths = [('A', 3u8), ('B', 3), ('C', 3), ('D', 3), ('E', 3), ('F', 2), ('G', 4), ('H', 4)];
     ///
-    /// let alphabet = HuffmanAlphabet::from_code_lengths(&code_lengths[..]);
+    /// let alphabet = HuffmanAlphabet::from_code_lengths(&code_lengths[..]).unwrap();
     /// let encoded = [0b11110111u8, 0b10111000];
     /// let mut bits = BitStream::new(&encoded[..]);
     /// assert_eq!(alphabet.read_next(&mut bits).unwrap(), 'G');
@@ -135,26 +231,86 @@ impl<'a, S: 'a + Copy + Ord> HuffmanAlphabet<S> {
     /// assert_eq!(alphabet.read_next(&mut bits).unwrap(), 'F');
     /// assert_eq!(alphabet.read_next(&mut bits).unwrap(), 'B');
     /// ```
-    pub fn read_next<R: Read>(&self, bits: &mut BitStream<R>) -> Result<S> {
-        let code = bits.peek_bits(self.max_code_length as usize, MsbFirst)? as u16;
-        assert!(code <= self.max_lut_code);
-        match self.lut[code as usize] {
-            None => bail!("Couldn't find match in lut for code {:b}", code),
-            Some(tree_idx) => {
-                let entry = &self.symbol_entries[tree_idx];
-                bits.skip_bits(entry.length as usize);
+    pub fn read_next<R: Read>(&self, bits: &mut BitStream<R>) -> Result<S, DeflateError> {
+        if self.symbol_entries.is_empty() {
+            return Err(DeflateError::EmptyAlphabet);
+        }
+        let code = match bits.peek_bits(self.max_code_length as usize, MsbFirst) {
+            Ok(code) => code as u16,
+            Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => {
+                return self.read_next_near_end(bits)
+            }
+            Err(e) => return Err(e.into()),
+        };
+        match self.lookup_entry(code) {
+            None => Err(DeflateError::InvalidCode(code)),
+            Some(entry) => {
+                bits.skip_bits(entry.length as usize)?;
                 Ok(entry.symbol)
             }
         }
     }
 
-    fn assign_codes(code_lengths: &[(S, u8)], max_code_length: u8) -> Vec<SymbolEntry<S>> {
-        let mut bl_count = vec![0; max_code_length as usize + 1];
+    // Near the end of the stream there may be fewer than `max_code_length` bits left, but still
+    // enough for a shorter code, e.g. the end of block symbol of the last block
+    fn read_next_near_end<R: Read>(&self, bits: &mut BitStream<R>) -> Result<S, DeflateError> {
+        for n_bits in (1..self.max_code_length).rev() {
+            if let Ok(partial_code) = bits.peek_bits(n_bits as usize, MsbFirst) {
+                let code = (partial_code as u16) << (self.max_code_length - n_bits);
+                return match self.lookup_entry(code) {
+                    Some(entry) if entry.length <= n_bits => {
+                        bits.skip_bits(entry.length as usize)?;
+                        Ok(entry.symbol)
+                    }
+                    _ => Err(DeflateError::Truncated),
+                };
+            }
+        }
+        Err(DeflateError::Truncated)
+    }
+
+    /// The code and code length of `symbol`, for encoding it
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use compression::deflate::HuffmanAlphabet;
+    ///
+    /// let code_lengths = [('A', 3u8), ('B', 3), ('C', 3), ('D', 3), ('E', 3), ('F', 2), ('G', 4), ('H', 4)];
+    ///
+    /// let alphabet = HuffmanAlphabet::from_code_lengths(&code_lengths[..]).unwrap();
+    /// assert_eq!(alphabet.code('F'), Some((0b00, 2)));
+    /// assert_eq!(alphabet.code('B'), Some((0b011, 3)));
+    /// assert_eq!(alphabet.code('H'), Some((0b1111, 4)));
+    /// assert_eq!(alphabet.code('I'), None);
+    /// ```
+    pub fn code(&self, symbol: S) -> Option<(u16, u8)> {
+        self.symbol_entries
+            .iter()
+            .find(|entry| entry.symbol == symbol)
+            .map(|entry| (entry.code, entry.length))
+    }
+
+    fn assign_codes(
+        code_lengths: &[(S, u8)],
+        max_code_length: u8,
+    ) -> Result<Vec<SymbolEntry<S>>, DeflateError> {
+        let mut bl_count = vec![0u16; max_code_length as usize + 1];
         code_lengths.iter().for_each(|&(_, x)| {
             bl_count[x as usize] += 1;
         });
         bl_count[0] = 0;
 
+        // Kraft inequality: the codes of each length must fit in what the shorter codes leave
+        // free. Incomplete codes are fine, e.g. a single distance code.
+        let mut codes_left: i32 = 1;
+        for &count in &bl_count[1..] {
+            codes_left = 2 * codes_left - count as i32;
+            if codes_left < 0 {
+                return Err(DeflateError::OverSubscribedCodeLengths);
+            }
+        }
+
         let mut next_code = vec![0u16; bl_count.len() + 1];
         let mut code = 0;
         for bits in 1..bl_count.len() + 1 {
@@ -178,20 +334,35 @@ impl<'a, S: 'a + Copy + Ord> HuffmanAlphabet<S> {
                 next_code[len as usize] += 1;
             }
         }
-        tree
+        Ok(tree)
     }
 }
 
 pub fn copy_dynamic_huffman_block<R: Read>(
     bits: &mut BitStream<R>,
     out_buf: &mut Vec<u8>,
-) -> Result<()> {
+    max_len: usize,
+) -> Result<(), DeflateError> {
+    let (literal_alphabet, distance_alphabet) = read_dynamic_alphabets(bits)?;
+    copy_huffman_block(
+        bits,
+        out_buf,
+        &literal_alphabet,
+        &distance_alphabet,
+        max_len,
+    )
+}
+
+/// Read the literal/length and distance alphabets from the start of a dynamic Huffman block
+pub(super) fn read_dynamic_alphabets<R: Read>(
+    bits: &mut BitStream<R>,
+) -> Result<(HuffmanAlphabet<u16>, HuffmanAlphabet<u16>), DeflateError> {
     let hlit = (bits.read_bits(5, LsbFirst)? + 257) as usize;
-    assert!((257..=286).contains(&hlit));
+    if hlit > 286 {
+        return Err(DeflateError::TooManyLiteralCodes(hlit));
+    }
     let hdist = (bits.read_bits(5, LsbFirst)? + 1) as usize;
-    assert!((1..=32).contains(&hdist));
     let hclen = (bits.read_bits(4, LsbFirst)? + 4) as usize;
-    assert!((4..=19).contains(&hclen));
 
     let mut code_lengths = vec![(0u8, 0u8); 19];
     for i in 0..hclen {
@@ -201,24 +372,24 @@ pub fn copy_dynamic_huffman_block<R: Read>(
         );
     }
 
-    let cl_alphabet = HuffmanAlphabet::from_code_lengths(&code_lengths);
-    println!("cl_alphabet {:?}", cl_alphabet);
+    let cl_alphabet = HuffmanAlphabet::from_code_lengths(&code_lengths)?;
 
     let literal_alphabet = extract_alphabet(bits, hlit, &cl_alphabet)?;
     let distance_alphabet = extract_alphabet(bits, hdist, &cl_alphabet)?;
-
-    copy_huffman_block(bits, out_buf, &literal_alphabet, &distance_alphabet)
+    Ok((literal_alphabet, distance_alphabet))
 }
 
 pub fn copy_static_huffman_block<R: Read>(
     bits: &mut BitStream<R>,
     out_buf: &mut Vec<u8>,
-) -> Result<()> {
+    max_len: usize,
+) -> Result<(), DeflateError> {
     copy_huffman_block(
         bits,
         out_buf,
         &STATIC_LITERAL_ALPHABET,
         &STATIC_DISTANCE_ALPHABET,
+        max_len,
     )
 }
 
@@ -227,24 +398,25 @@ fn copy_huffman_block<R: Read>(
     out_buf: &mut Vec<u8>,
     literal_alphabet: &HuffmanAlphabet<u16>,
     distance_alphabet: &HuffmanAlphabet<u16>,
-) -> Result<(), Error> {
+    max_len: usize,
+) -> Result<(), DeflateError> {
     loop {
         use DeflateSymbol::*;
 
         let symbol = read_deflate_symbol(bits, &literal_alphabet, &distance_alphabet)?;
         match symbol {
             Literal(value) => {
+                if out_buf.len() >= max_len {
+                    return Err(DeflateError::OutputLimitExceeded { max_len });
+                }
                 out_buf.push(value);
             }
             LengthAndDistance(length, distance) => {
+                if out_buf.len() + length as usize > max_len {
+                    return Err(DeflateError::OutputLimitExceeded { max_len });
+                }
                 let current_idx = out_buf.len();
-                assert!(
-                    distance as usize <= current_idx,
-                    "length={}, distance {} > current_idx {}",
-                    length,
-                    distance,
-                    current_idx
-                );
+                check_distance(distance, current_idx)?;
                 let copy_start = current_idx - distance as usize;
                 let copy_end = copy_start + length as usize;
                 for idx in copy_start..copy_end {
@@ -259,6 +431,18 @@ fn copy_huffman_block<R: Read>(
     Ok(())
 }
 
+/// Check that a back-reference `distance` stays within the `available` bytes. The window needs no
+/// separate check, as the largest distance code decodes to exactly `WINDOW_SIZE`.
+pub(super) fn check_distance(distance: u16, available: usize) -> Result<(), DeflateError> {
+    if distance as usize > available {
+        return Err(DeflateError::InvalidDistance {
+            distance,
+            available,
+        });
+    }
+    Ok(())
+}
+
 enum ExtractAction {
     CodeLength(u8),
     CopyLastLength(u8),
@@ -269,7 +453,7 @@ impl ExtractAction {
     fn from_bit_stream<R: Read>(
         bits: &mut BitStream<R>,
         alphabet: &HuffmanAlphabet<u8>,
-    ) -> Result<ExtractAction> {
+    ) -> Result<ExtractAction, DeflateError> {
         use ExtractAction::*;
         let s = alphabet.read_next(bits)?;
         match s {
@@ -286,7 +470,7 @@ impl ExtractAction {
                 let zero_times = bits.read_bits(7, LsbFirst)? + 11;
                 Ok(RepeatZero(zero_times as u8))
             }
-            _ => bail!("Invalid literal code length symbol: {}", s),
+            _ => Err(DeflateError::InvalidCodeLengthSymbol(s)),
         }
     }
 }
@@ -295,10 +479,9 @@ pub fn extract_alphabet<R: Read>(
     bits: &mut BitStream<R>,
     alphabet_size: usize,
     cl_alphabet: &HuffmanAlphabet<u8>,
-) -> Result<HuffmanAlphabet<u16>> {
+) -> Result<HuffmanAlphabet<u16>, DeflateError> {
     let mut literal_code_lengths = Vec::new();
     let mut cl_symbol: u16 = 0;
-    println!("hlit = {}", alphabet_size);
     while (cl_symbol as usize) < alphabet_size {
         match ExtractAction::from_bit_stream(bits, cl_alphabet)? {
             ExtractAction::CodeLength(length) => {
@@ -313,19 +496,18 @@ pub fn extract_alphabet<R: Read>(
             }
         }
     }
-    println!("cl_symbol at end {}", cl_symbol);
 
-    Ok(HuffmanAlphabet::from_code_lengths(&literal_code_lengths))
+    HuffmanAlphabet::from_code_lengths(&literal_code_lengths)
 }
 
 fn copy_last_length(
     times: u8,
     literal_code_lengths: &mut Vec<(u16, u8)>,
     cl_symbol: &mut u16,
-) -> Result<()> {
+) -> Result<(), DeflateError> {
     let last_code = literal_code_lengths.last();
     match last_code {
-        None => bail!("No last element in literal_code_lengths"),
+        None => Err(DeflateError::MissingPreviousCodeLength),
         Some(&(_symbol, length)) => {
             for _ in 0..times {
                 literal_code_lengths.push((*cl_symbol, length));
@@ -343,17 +525,17 @@ fn repeat_zero(times: u8, literal_code_lengths: &mut Vec<(u16, u8)>, cl_symbol:
 }
 
 #[derive(Debug, Clone, Copy, PartialEq)]
-enum DeflateSymbol {
+pub(super) enum DeflateSymbol {
     Literal(u8),
     LengthAndDistance(u16, u16),
     EndOfData,
 }
 
-fn read_deflate_symbol<R: Read>(
+pub(super) fn read_deflate_symbol<R: Read>(
     bits: &mut BitStream<R>,
     literal_alphabet: &HuffmanAlphabet<u16>,
     distance_alphabet: &HuffmanAlphabet<u16>,
-) -> Result<DeflateSymbol> {
+) -> Result<DeflateSymbol, DeflateError> {
     use DeflateSymbol::*;
 
     let raw_symbol = literal_alphabet.read_next(bits)?;
@@ -365,11 +547,11 @@ fn read_deflate_symbol<R: Read>(
             raw_symbol,
             distance_alphabet,
         )?),
-        _ => bail!("Invalid Deflate symbol {}", raw_symbol),
+        _ => Err(DeflateError::InvalidSymbol(raw_symbol)),
     }
 }
 
-static LENGTH_EXTRA_BITS: [usize; 29] = [
+pub(super) static LENGTH_EXTRA_BITS: [usize; 29] = [
     0, 0, 0, 0, 0, 0, 0, 0, // 257 - 264
     1, 1, 1, 1, //             265 - 268
     2, 2, 2, 2, //             269 - 272
@@ -379,7 +561,7 @@ static LENGTH_EXTRA_BITS: [usize; 29] = [
     0, //                      285
 ];
 
-static BASE_LENGTH: [u16; 29] = [
+pub(super) static BASE_LENGTH: [u16; 29] = [
     3, 4, 5, 6, 7, 8, 9, 10, // 0 extra bits
     11, 13, 15, 17, //          1 extra bit
     19, 23, 27, 31, //          2 extra bits
@@ -393,7 +575,7 @@ fn read_length_and_distance<R: Read>(
     bits: &mut BitStream<R>,
     length_symbol: u16,
     distance_alphabet: &HuffmanAlphabet<u16>,
-) -> Result<DeflateSymbol> {
+) -> Result<DeflateSymbol, DeflateError> {
     use DeflateSymbol::*;
 
     let length = read_length(bits, length_symbol)?;
@@ -401,19 +583,19 @@ fn read_length_and_distance<R: Read>(
     Ok(LengthAndDistance(length, distance))
 }
 
-fn read_length<R: Read>(bits: &mut BitStream<R>, length_symbol: u16) -> Result<u16> {
+fn read_length<R: Read>(bits: &mut BitStream<R>, length_symbol: u16) -> Result<u16, DeflateError> {
     let lut_idx = (length_symbol - 257) as usize;
     let extra_bits = LENGTH_EXTRA_BITS[lut_idx];
     let base_length = BASE_LENGTH[lut_idx];
     Ok(base_length + bits.read_bits(extra_bits, LsbFirst)? as u16)
 }
 
-static DISTANCE_EXTRA_BITS: [usize; 30] = [
+pub(super) static DISTANCE_EXTRA_BITS: [usize; 30] = [
     0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13,
     13,
 ];
 
-static BASE_DISTANCE: [u16; 30] = [
+pub(super) static BASE_DISTANCE: [u16; 30] = [
     1, 2, 3, 4, //   0 extra bits
     5, 7, //         1 extra bit
     9, 13, //        2 extra bits
@@ -433,8 +615,11 @@ static BASE_DISTANCE: [u16; 30] = [
 fn read_distance<R: Read>(
     bits: &mut BitStream<R>,
     distance_alphabet: &HuffmanAlphabet<u16>,
-) -> Result<u16> {
+) -> Result<u16, DeflateError> {
     let raw_distance = distance_alphabet.read_next(bits)? as usize;
+    if raw_distance >= BASE_DISTANCE.len() {
+        return Err(DeflateError::InvalidDistanceSymbol(raw_distance as u16));
+    }
     let extra_bits = DISTANCE_EXTRA_BITS[raw_distance];
     let base_distance = BASE_DISTANCE[raw_distance];
     Ok(base_distance + bits.read_bits(extra_bits, LsbFirst)? as u16)
@@ -546,6 +731,96 @@ mod tests {
         assert_length(258, 285, &bytes);
     }
 
+    // Code lengths 1, 2, ..., 14, 15, 15 form a complete prefix code with 15-bit codes
+    fn long_code_lengths() -> Vec<(u16, u8)> {
+        (1u8..=15)
+            .map(|len| (len as u16, len))
+            .chain(iter::once((16, 15)))
+            .collect()
+    }
+
+    #[test]
+    fn test_empty_alphabet() {
+        // All-zero distance code lengths are allowed for blocks containing only literals
+        let alphabet = HuffmanAlphabet::from_code_lengths(&[(0u16, 0u8); 30]).unwrap();
+        let bytes = [0u8; 4];
+        let mut bits = BitStream::new(&bytes[..]);
+        assert!(matches!(
+            alphabet.read_next(&mut bits),
+            Err(DeflateError::EmptyAlphabet)
+        ));
+    }
+
+    #[test]
+    fn test_two_level_lut() {
+        let code_lengths = long_code_lengths();
+        let flat = HuffmanAlphabet::with_root_lut_bits(&code_lengths, 15).unwrap();
+        let two_level = HuffmanAlphabet::from_code_lengths(&code_lengths).unwrap();
+
+        assert_eq!(flat.lut.len(), 1 << 15);
+        assert!(two_level.lut.len() < 1 << 10);
+
+        for code in 0..=flat.max_lut_code {
+            assert_eq!(flat.lookup(code), two_level.lookup(code), "code {:b}", code);
+        }
+    }
+
+    #[test]
+    fn test_two_level_lut_read_next() {
+        let code_lengths = long_code_lengths();
+        let flat = HuffmanAlphabet::with_root_lut_bits(&code_lengths, 15).unwrap();
+        let two_level = HuffmanAlphabet::from_code_lengths(&code_lengths).unwrap();
+
+        // Pseudo-random bytes, padded so that peeking 15 bits never runs out of input
+        let mut bytes: Vec<u8> = (0u32..4096).map(|i| (i * 7919 % 251) as u8).collect();
+        bytes.extend_from_slice(&[0; 4]);
+        let n_bytes = bytes.len() - 4;
+
+        let mut flat_bits = BitStream::new(&bytes[..]);
+        let mut two_level_bits = BitStream::new(&bytes[..]);
+        let mut n_bits_read = 0;
+        while n_bits_read < 8 * n_bytes {
+            let symbol = flat.read_next(&mut flat_bits).unwrap();
+            assert_eq!(symbol, two_level.read_next(&mut two_level_bits).unwrap());
+            n_bits_read += code_lengths[symbol as usize - 1].1 as usize;
+        }
+    }
+
+    #[test]
+    fn test_over_subscribed_code_lengths() {
+        // One 15-bit code too many for a complete code, both within and past the root table
+        let mut code_lengths = long_code_lengths();
+        code_lengths.push((17, 15));
+        for max_root_lut_bits in [9, 15] {
+            assert!(matches!(
+                HuffmanAlphabet::with_root_lut_bits(&code_lengths, max_root_lut_bits),
+                Err(DeflateError::OverSubscribedCodeLengths)
+            ));
+        }
+
+        let code_lengths = [(0u16, 1u8), (1, 2), (2, 2), (3, 2)];
+        assert!(matches!(
+            HuffmanAlphabet::from_code_lengths(&code_lengths),
+            Err(DeflateError::OverSubscribedCodeLengths)
+        ));
+
+        // Incomplete codes are allowed
+        let code_lengths = [(0u16, 1u8), (1, 3)];
+        let alphabet = HuffmanAlphabet::from_code_lengths(&code_lengths).unwrap();
+        assert_eq!(alphabet.lookup(0b011), Some(0));
+        assert_eq!(alphabet.lookup(0b100), Some(1));
+        assert_eq!(alphabet.lookup(0b101), None);
+    }
+
+    #[test]
+    fn test_code_length_too_long() {
+        let code_lengths = [(0u16, 1u8), (1, 16)];
+        assert!(matches!(
+            HuffmanAlphabet::from_code_lengths(&code_lengths),
+            Err(DeflateError::CodeLengthTooLong(16))
+        ));
+    }
+
     fn assert_length(expected_length: u16, length_code: u16, bytes: &[u8]) {
         let mut bits = BitStream::new(bytes);
         let length = read_length(&mut bits, length_code);
@@ -1,10 +1,126 @@
+mod encode;
 mod huffman;
+mod reader;
 
+pub use encode::{compress_fixed, compress_stored};
 pub use huffman::HuffmanAlphabet;
+pub use reader::DeflateReader;
 
-use anyhow::{bail, Result};
 use fiddling::*;
 use std::io::{Read, Write};
+use std::{error, fmt, io, mem};
+
+/// The ways decompressing a deflate stream can fail
+#[derive(Debug)]
+pub enum DeflateError {
+    /// A block header with the reserved block type `0b11`
+    ReservedBlockType,
+    /// The LEN and NLEN fields of an uncompressed block aren't one's complements of each other
+    LengthMismatch {
+        len: u16,
+        nlen: u16,
+    },
+    /// A code that isn't part of the Huffman alphabet
+    InvalidCode(u16),
+    /// A literal/length symbol outside 0..=285
+    InvalidSymbol(u16),
+    /// A distance symbol outside 0..=29
+    InvalidDistanceSymbol(u16),
+    /// A back-reference to before the start of the data
+    InvalidDistance {
+        distance: u16,
+        available: usize,
+    },
+    /// A dynamic Huffman block header with more than 286 literal/length codes
+    TooManyLiteralCodes(usize),
+    /// A symbol read with a Huffman alphabet that has no codes, e.g. a distance in a block
+    /// whose distance code lengths are all zero
+    EmptyAlphabet,
+    /// A code length symbol outside 0..=18
+    InvalidCodeLengthSymbol(u8),
+    /// A "copy the previous code length" symbol before any code lengths
+    MissingPreviousCodeLength,
+    /// Code lengths with more codes than fit in a prefix code
+    OverSubscribedCodeLengths,
+    /// A code length over the 15 bits Deflate allows
+    CodeLengthTooLong(u8),
+    /// The input ended in the middle of the stream
+    Truncated,
+    /// The decompressed data would be longer than the allowed maximum
+    OutputLimitExceeded {
+        max_len: usize,
+    },
+    Io(io::Error),
+}
+
+impl fmt::Display for DeflateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use DeflateError::*;
+        match self {
+            ReservedBlockType => write!(f, "Invalid compression type, Reserved"),
+            LengthMismatch { len, nlen } => write!(f, "LEN {} doesn't match NLEN {}", len, nlen),
+            InvalidCode(code) => write!(f, "Couldn't find match in lut for code {:b}", code),
+            InvalidSymbol(symbol) => write!(f, "Invalid Deflate symbol {}", symbol),
+            InvalidDistanceSymbol(symbol) => write!(f, "Invalid distance symbol {}", symbol),
+            InvalidDistance {
+                distance,
+                available,
+            } => write!(
+                f,
+                "Distance {} exceeds the {} bytes available for back-references",
+                distance, available
+            ),
+            TooManyLiteralCodes(hlit) => {
+                write!(f, "{} literal/length codes, expected at most 286", hlit)
+            }
+            EmptyAlphabet => write!(f, "No codes in the Huffman alphabet"),
+            InvalidCodeLengthSymbol(symbol) => {
+                write!(f, "Invalid literal code length symbol: {}", symbol)
+            }
+            MissingPreviousCodeLength => write!(f, "No previous code length to copy"),
+            OverSubscribedCodeLengths => write!(f, "Over-subscribed Huffman code lengths"),
+            CodeLengthTooLong(length) => write!(f, "Code length {} exceeds 15 bits", length),
+            Truncated => write!(f, "Unexpected end of deflate stream"),
+            OutputLimitExceeded { max_len } => {
+                write!(
+                    f,
+                    "Decompressed data exceeds the limit of {} bytes",
+                    max_len
+                )
+            }
+            Io(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl error::Error for DeflateError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            DeflateError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for DeflateError {
+    fn from(e: io::Error) -> Self {
+        if e.kind() == io::ErrorKind::UnexpectedEof {
+            DeflateError::Truncated
+        } else {
+            DeflateError::Io(e)
+        }
+    }
+}
+
+impl From<DeflateError> for io::Error {
+    fn from(e: DeflateError) -> Self {
+        match e {
+            DeflateError::Io(e) => e,
+            DeflateError::Truncated => io::ErrorKind::UnexpectedEof.into(),
+            e => io::Error::new(io::ErrorKind::InvalidData, e),
+        }
+    }
+}
 
 #[derive(PartialEq, Debug)]
 enum CompressionType {
@@ -40,64 +156,413 @@ impl From<u8> for BlockHeader {
 }
 
 // Return the three block header bits as
-fn read_block_header<R: Read>(bits: &mut BitStream<R>) -> Result<BlockHeader> {
+fn read_block_header<R: Read>(bits: &mut BitStream<R>) -> Result<BlockHeader, DeflateError> {
     let header_bits = bits.read_bits(3, BitOrder::LsbFirst)?;
     Ok(BlockHeader::from(header_bits as u8))
 }
 
-fn copy_bytes<R: Read, W: Write>(r: &mut R, w: &mut W) -> Result<()> {
+// Copy bytes from `r` to `w` until `r` is exhausted, returning the number of bytes copied
+fn copy_bytes<R: Read, W: Write>(r: &mut R, w: &mut W) -> Result<u64, DeflateError> {
     let mut buf = [0u8; 1024];
+    let mut bytes_copied = 0;
     let mut bytes_written = r.read(&mut buf[..])?;
     while bytes_written != 0 {
         w.write_all(&buf[..bytes_written])?;
+        bytes_copied += bytes_written as u64;
         bytes_written = r.read(&mut buf[..])?;
     }
-    Ok(())
+    Ok(bytes_copied)
 }
 
-fn copy_uncompressed_block<R: Read, W: Write>(
+fn copy_uncompressed_block<R: Read>(
     bits: &mut BitStream<R>,
-    out_bytes: &mut W,
-) -> Result<()> {
+    out_bytes: &mut Vec<u8>,
+    max_len: usize,
+) -> Result<(), DeflateError> {
     bits.skip_to_start_of_byte();
 
     let len = bits.read_u16_le()?;
     let nlen = bits.read_u16_le()?;
 
-    if len & nlen != 0 {
-        bail!("LEN & NLEN != 0");
+    if len != !nlen {
+        return Err(DeflateError::LengthMismatch { len, nlen });
+    }
+    if out_bytes.len() + len as usize > max_len {
+        return Err(DeflateError::OutputLimitExceeded { max_len });
     }
 
     let mut bytes_to_read = bits.get_mut().take(len as u64);
-    copy_bytes(&mut bytes_to_read, out_bytes)?;
+    if copy_bytes(&mut bytes_to_read, out_bytes)? < len as u64 {
+        return Err(DeflateError::Truncated);
+    }
     Ok(())
 }
 
-pub fn decompress_blocks(in_bytes: &[u8], out_buf: &mut Vec<u8>) -> Result<()> {
+/// The largest back-reference distance allowed by deflate
+pub const WINDOW_SIZE: usize = 32768;
+
+/// The back-reference window of a deflate stream, i.e. the last `WINDOW_SIZE` decompressed bytes.
+///
+/// Passing the same window to consecutive calls of `decompress_with_window` lets later streams
+/// refer back to data decompressed by earlier ones.
+#[derive(Debug, Default, Clone)]
+pub struct Window {
+    bytes: Vec<u8>,
+}
+
+impl Window {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a window preset with the last `WINDOW_SIZE` bytes of `bytes`.
+    pub fn from_bytes(bytes: &[u8]) -> Self {
+        let mut window = Self::new();
+        window.push(bytes);
+        window
+    }
+
+    pub fn as_slice(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    fn push(&mut self, bytes: &[u8]) {
+        self.bytes.extend_from_slice(bytes);
+        if self.bytes.len() > WINDOW_SIZE {
+            self.bytes.drain(..self.bytes.len() - WINDOW_SIZE);
+        }
+    }
+}
+
+/// Decompress deflate blocks starting with the back-reference state in `window`, writing the
+/// decompressed bytes to `out` and updating `window` with them. Unlike `decompress_blocks`,
+/// `in_bytes` may also end right after a non-final block, e.g. at a flush point, so that the
+/// rest of the stream can be decompressed by another call with the same window.
+pub fn decompress_with_window<W: Write>(
+    in_bytes: &[u8],
+    window: &mut Window,
+    out: &mut W,
+) -> Result<(), DeflateError> {
+    decompress_with_window_until(in_bytes, window, out, true).map(|_| ())
+}
+
+// Like `decompress_with_window`, requiring a final block unless `allow_non_final`. Returns the
+// number of bytes of `in_bytes` taken by the blocks.
+pub(crate) fn decompress_with_window_until<W: Write>(
+    in_bytes: &[u8],
+    window: &mut Window,
+    out: &mut W,
+    allow_non_final: bool,
+) -> Result<usize, DeflateError> {
+    let window_len = window.bytes.len();
+    let mut buf = mem::take(&mut window.bytes);
+    let result = decompress_blocks_until(in_bytes, &mut buf, usize::MAX, allow_non_final);
+
+    let decompressed = buf.split_off(window_len);
+    window.bytes = buf;
+    let len = result?;
+
+    out.write_all(&decompressed)?;
+    window.push(&decompressed);
+    Ok(len)
+}
+
+pub fn decompress_blocks(in_bytes: &[u8], out_buf: &mut Vec<u8>) -> Result<(), DeflateError> {
+    decompress_blocks_limited(in_bytes, out_buf, usize::MAX)
+}
+
+/// Like `decompress_blocks`, but fails with `DeflateError::OutputLimitExceeded` instead of
+/// growing `out_buf` past `max_len` bytes, e.g. to guard against decompression bombs in
+/// untrusted input. `out_buf` is left with what was decompressed before hitting the limit.
+///
+/// # Examples
+///
+/// ```rust
+/// use compression::deflate::{compress_fixed, decompress_blocks_limited, DeflateError};
+///
+/// let mut compressed = Vec::new();
+/// compress_fixed(&[0; 10000], &mut compressed).unwrap();
+///
+/// let mut out = Vec::new();
+/// assert!(decompress_blocks_limited(&compressed, &mut out, 10000).is_ok());
+///
+/// let mut out = Vec::new();
+/// assert!(matches!(
+///     decompress_blocks_limited(&compressed, &mut out, 9999),
+///     Err(DeflateError::OutputLimitExceeded { max_len: 9999 })
+/// ));
+/// assert!(out.len() <= 9999);
+/// ```
+pub fn decompress_blocks_limited(
+    in_bytes: &[u8],
+    out_buf: &mut Vec<u8>,
+    max_len: usize,
+) -> Result<(), DeflateError> {
+    decompress_blocks_until(in_bytes, out_buf, max_len, false).map(|_| ())
+}
+
+// Decompress blocks until the final one, or with `allow_non_final` also until the end of
+// `in_bytes` if it comes right after a block. Returns the number of bytes of `in_bytes` taken by
+// the blocks, i.e. where the data after the deflate stream starts.
+pub(crate) fn decompress_blocks_until(
+    in_bytes: &[u8],
+    out_buf: &mut Vec<u8>,
+    max_len: usize,
+    allow_non_final: bool,
+) -> Result<usize, DeflateError> {
     use CompressionType::*;
     let mut bits = BitStream::new(in_bytes);
     'block: loop {
         let block_header = read_block_header(&mut bits)?;
 
-        println!("{:?}", block_header.compression_type);
         match block_header.compression_type {
             NoCompression => {
-                copy_uncompressed_block(&mut bits, out_buf)?;
+                copy_uncompressed_block(&mut bits, out_buf, max_len)?;
             }
             FixedHuffman => {
-                huffman::copy_static_huffman_block(&mut bits, out_buf)?;
+                huffman::copy_static_huffman_block(&mut bits, out_buf, max_len)?;
             }
             DynamicHuffman => {
-                huffman::copy_dynamic_huffman_block(&mut bits, out_buf)?;
+                huffman::copy_dynamic_huffman_block(&mut bits, out_buf, max_len)?;
             }
-            Reserved => bail!("Invalid compression type, Reserved"),
+            Reserved => return Err(DeflateError::ReservedBlockType),
         }
 
         if block_header.is_final {
-            println!("Final block! We're done!");
+            break 'block;
+        }
+        // Only the padding of the last byte left, with nothing more buffered from `in_bytes`
+        if allow_non_final && bits.readable_bits() < 8 && bits.get_ref().is_empty() {
             break 'block;
         }
     }
 
-    Ok(())
+    // The unread bits still buffered are the padding of the last byte and whole bytes after it
+    Ok(in_bytes.len() - bits.get_ref().len() - bits.readable_bits() / 8)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // "Hello again, window! Hello, window!", compressed as a raw deflate stream with "Hello,
+    // window! " repeated four times as preset dictionary
+    const SECOND: [u8; 12] = [3, 115, 21, 18, 211, 19, 51, 243, 112, 169, 1, 0];
+
+    #[test]
+    fn test_decompress_with_window() {
+        // A non-final stored block followed by a block referring back to it
+        let first = b"Hello, window! ".repeat(4);
+        let mut compressed = vec![0];
+        compressed.extend_from_slice(&(first.len() as u16).to_le_bytes());
+        compressed.extend_from_slice(&(!(first.len() as u16)).to_le_bytes());
+        compressed.extend_from_slice(&first);
+        let split = compressed.len();
+        compressed.extend_from_slice(&SECOND);
+
+        let mut expected = Vec::new();
+        decompress_blocks(&compressed, &mut expected).unwrap();
+        let mut second = first.clone();
+        second.extend_from_slice(b"Hello again, window! Hello, window!");
+        assert_eq!(expected, second);
+
+        let mut window = Window::new();
+        let mut out = Vec::new();
+        decompress_with_window(&compressed[..split], &mut window, &mut out).unwrap();
+        assert_eq!(out, first);
+        decompress_with_window(&compressed[split..], &mut window, &mut out).unwrap();
+        assert_eq!(out, expected);
+        assert_eq!(window.as_slice(), &expected[..]);
+
+        // Not at a block boundary
+        let mut out = Vec::new();
+        assert!(matches!(
+            decompress_with_window(&compressed[..split - 1], &mut Window::new(), &mut out),
+            Err(DeflateError::Truncated)
+        ));
+        // Without a window the second block refers to data before the stream
+        assert!(
+            decompress_with_window(&compressed[split..], &mut Window::new(), &mut out).is_err()
+        );
+
+        // decompress_blocks still wants the final block
+        let mut out = Vec::new();
+        assert!(decompress_blocks(&compressed[..split], &mut out).is_err());
+    }
+
+    #[test]
+    fn test_fixed_huffman() {
+        // "Hello, window! " repeated four times, as a single fixed Huffman block
+        let compressed = [
+            243, 72, 205, 201, 201, 215, 81, 40, 207, 204, 75, 201, 47, 87, 84, 240, 32, 133, 11, 0,
+        ];
+        assert_eq!(
+            read_block_header(&mut BitStream::new(&compressed[..])).unwrap(),
+            BlockHeader {
+                is_final: true,
+                compression_type: CompressionType::FixedHuffman
+            }
+        );
+        let mut out = Vec::new();
+        decompress_blocks(&compressed, &mut out).unwrap();
+        assert_eq!(out, "Hello, window! ".repeat(4).as_bytes());
+    }
+
+    #[test]
+    fn test_reserved_block_type() {
+        // BFINAL = 1, BTYPE = 0b11
+        let mut out = Vec::new();
+        assert!(matches!(
+            decompress_blocks(&[0b0000_0111], &mut out),
+            Err(DeflateError::ReservedBlockType)
+        ));
+    }
+
+    #[test]
+    fn test_truncated() {
+        // BFINAL = 1, BTYPE = 0b00, LEN = 5 but only two bytes follow
+        let mut out = Vec::new();
+        assert!(matches!(
+            decompress_blocks(&[0b0000_0001, 5, 0, !5, !0, b'a', b'b'], &mut out),
+            Err(DeflateError::Truncated)
+        ));
+    }
+
+    #[test]
+    fn test_window_keeps_last_bytes() {
+        let bytes: Vec<u8> = (0..WINDOW_SIZE + 10).map(|i| i as u8).collect();
+        let window = Window::from_bytes(&bytes);
+        assert_eq!(window.as_slice(), &bytes[10..]);
+    }
+
+    #[test]
+    fn test_decompress_blocks_limited() {
+        let input: Vec<u8> = (0u32..70_000).map(|i| (i % 251) as u8).collect();
+        let mut fixed = Vec::new();
+        compress_fixed(&input, &mut fixed).unwrap();
+        let mut stored = Vec::new();
+        compress_stored(&input, &mut stored);
+
+        for compressed in &[fixed, stored] {
+            let mut out = Vec::new();
+            decompress_blocks_limited(compressed, &mut out, input.len()).unwrap();
+            assert!(out == input);
+
+            for &max_len in &[0, 1, 1000, 65535, input.len() - 1] {
+                let mut out = Vec::new();
+                let result = decompress_blocks_limited(compressed, &mut out, max_len);
+                assert!(
+                    matches!(result, Err(DeflateError::OutputLimitExceeded { max_len: m }) if m == max_len),
+                    "max_len {}",
+                    max_len
+                );
+                assert!(out.len() <= max_len);
+            }
+        }
+    }
+
+    // A dynamic Huffman block whose first symbols are a literal followed by a back-reference of
+    // distance 32768, way past the single byte written so far
+    fn distance_past_start_block() -> Vec<u8> {
+        use fiddling::BitOrder::{LsbFirst, MsbFirst};
+
+        let mut w = BitWriter::new(Vec::new());
+        let mut write = |value: u64, n: usize, bo| w.write_bits(value, n, bo).unwrap();
+        // BFINAL = 1, BTYPE = 0b10, HLIT = 258 - 257, HDIST = 30 - 1, HCLEN = 18 - 4
+        write(1, 1, LsbFirst);
+        write(0b10, 2, LsbFirst);
+        write(1, 5, LsbFirst);
+        write(29, 5, LsbFirst);
+        write(14, 4, LsbFirst);
+        // Code length code lengths in the order 16, 17, 18, 0, 8, 7, 9, 6, 10, 5, 11, 4, 12, 3,
+        // 13, 2, 14, 1, giving 18 => 0, 1 => 10 and 2 => 11
+        for &length in &[0, 0, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 2, 0, 2] {
+            write(length, 3, LsbFirst);
+        }
+        // Literal/length code lengths: 0 => 1, 1..=255 => 0, 256 => 2, 257 => 2
+        write(0b10, 2, MsbFirst);
+        write(0b0, 1, MsbFirst);
+        write(138 - 11, 7, LsbFirst);
+        write(0b0, 1, MsbFirst);
+        write(117 - 11, 7, LsbFirst);
+        write(0b11, 2, MsbFirst);
+        write(0b11, 2, MsbFirst);
+        // Distance code lengths: 0..=28 => 0, 29 => 1
+        write(0b0, 1, MsbFirst);
+        write(29 - 11, 7, LsbFirst);
+        write(0b10, 2, MsbFirst);
+        // Literal 0, then length 3 (symbol 257) at distance 24577 + 8191 = 32768
+        write(0b0, 1, MsbFirst);
+        write(0b11, 2, MsbFirst);
+        write(0b0, 1, MsbFirst);
+        write(0x1fff, 13, LsbFirst);
+        // End of block
+        write(0b10, 2, MsbFirst);
+        w.into_inner().unwrap()
+    }
+
+    #[test]
+    fn test_distance_past_start() {
+        let block = distance_past_start_block();
+        let mut out = Vec::new();
+        assert!(matches!(
+            decompress_blocks(&block, &mut out),
+            Err(DeflateError::InvalidDistance {
+                distance: 32768,
+                available: 1
+            })
+        ));
+
+        let mut out = Vec::new();
+        let e = DeflateReader::new(&block[..])
+            .read_to_end(&mut out)
+            .unwrap_err();
+        assert_eq!(e.kind(), io::ErrorKind::InvalidData);
+
+        // With a full window before it, the same back-reference, the longest possible, is fine
+        let mut window = Window::from_bytes(&[7; WINDOW_SIZE]);
+        let mut out = Vec::new();
+        decompress_with_window(&block, &mut window, &mut out).unwrap();
+        assert_eq!(out, [0, 7, 7, 7]);
+    }
+
+    #[test]
+    fn test_invalid_distance_symbol() {
+        // BFINAL = 1, BTYPE = 0b01, then length 3 (symbol 257 = 0000001) at distance code 30
+        let mut w = BitWriter::new(Vec::new());
+        w.write_bits(1, 1, BitOrder::LsbFirst).unwrap();
+        w.write_bits(0b01, 2, BitOrder::LsbFirst).unwrap();
+        w.write_bits(0b0000001, 7, BitOrder::MsbFirst).unwrap();
+        w.write_bits(30, 5, BitOrder::MsbFirst).unwrap();
+        let block = w.into_inner().unwrap();
+
+        let mut out = Vec::new();
+        assert!(matches!(
+            decompress_blocks(&block, &mut out),
+            Err(DeflateError::InvalidDistanceSymbol(30))
+        ));
+    }
+
+    #[test]
+    fn test_too_many_literal_codes() {
+        // BFINAL = 1, BTYPE = 0b10, HLIT = 288 - 257
+        let bytes = [0b1111_1101, 0, 0];
+        let mut out = Vec::new();
+        assert!(matches!(
+            decompress_blocks(&bytes, &mut out),
+            Err(DeflateError::TooManyLiteralCodes(288))
+        ));
+    }
+
+    #[test]
+    fn test_over_subscribed_code_lengths() {
+        let bytes = [
+            0x3c, 0xc2, 0x61, 0x15, 0x7f, 0x0b, 0x5b, 0x5b, 0x35, 0x67, 0xe9, 0x42, 0x2c, 0x34,
+        ];
+        let mut out = Vec::new();
+        assert!(matches!(
+            decompress_blocks(&bytes, &mut out),
+            Err(DeflateError::OverSubscribedCodeLengths)
+        ));
+    }
 }
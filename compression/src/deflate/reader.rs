@@ -0,0 +1,231 @@
+use super::huffman::{
+    self, DeflateSymbol, HuffmanAlphabet, STATIC_DISTANCE_ALPHABET, STATIC_LITERAL_ALPHABET,
+};
+use super::{read_block_header, CompressionType, DeflateError, WINDOW_SIZE};
+use fiddling::BitStream;
+use std::cmp;
+use std::io::{self, Read};
+
+// How many bytes of a stored block are copied at a time
+const STORED_CHUNK_SIZE: usize = 4096;
+
+enum Alphabets {
+    Fixed,
+    Dynamic(Box<(HuffmanAlphabet<u16>, HuffmanAlphabet<u16>)>),
+}
+
+impl Alphabets {
+    fn get(&self) -> (&HuffmanAlphabet<u16>, &HuffmanAlphabet<u16>) {
+        match self {
+            Alphabets::Fixed => (&STATIC_LITERAL_ALPHABET, &STATIC_DISTANCE_ALPHABET),
+            Alphabets::Dynamic(alphabets) => (&alphabets.0, &alphabets.1),
+        }
+    }
+}
+
+enum State {
+    BlockHeader,
+    Stored {
+        remaining: usize,
+        is_final: bool,
+    },
+    Huffman {
+        alphabets: Alphabets,
+        is_final: bool,
+    },
+    Done,
+}
+
+/// A streaming deflate decompressor, reading compressed bytes from `inner` as needed and
+/// decompressing one symbol at a time.
+///
+/// # Examples
+///
+/// ```rust
+/// use compression::deflate::DeflateReader;
+/// use std::io::Read;
+///
+/// // "Hello, window! " repeated four times
+/// let compressed = [
+///     243, 72, 205, 201, 201, 215, 81, 40, 207, 204, 75, 201, 47, 87, 84, 240, 32, 133, 11, 0,
+/// ];
+/// let mut reader = DeflateReader::new(&compressed[..]);
+/// let mut out = String::new();
+/// reader.read_to_string(&mut out).unwrap();
+/// assert_eq!(out, "Hello, window! ".repeat(4));
+/// ```
+pub struct DeflateReader<R> {
+    bits: BitStream<R>,
+    state: State,
+    // Decompressed bytes, i.e. the back-reference window followed by the ones not yet read
+    buf: Vec<u8>,
+    read_pos: usize,
+}
+
+impl<R: Read> DeflateReader<R> {
+    pub fn new(inner: R) -> DeflateReader<R> {
+        DeflateReader::from_bit_stream(BitStream::new(inner))
+    }
+
+    pub(crate) fn from_bit_stream(bits: BitStream<R>) -> DeflateReader<R> {
+        DeflateReader {
+            bits,
+            state: State::BlockHeader,
+            buf: Vec::new(),
+            read_pos: 0,
+        }
+    }
+
+    /// Whether the final block has been decompressed. There may still be bytes left to read.
+    pub fn is_done(&self) -> bool {
+        matches!(self.state, State::Done)
+    }
+
+    /// The bits after the deflate stream, once `is_done`
+    pub(crate) fn bits_mut(&mut self) -> &mut BitStream<R> {
+        &mut self.bits
+    }
+
+    fn end_of_block(is_final: bool) -> State {
+        if is_final {
+            State::Done
+        } else {
+            State::BlockHeader
+        }
+    }
+
+    // Decompress a block header, a chunk of a stored block or a single Huffman symbol
+    fn step(&mut self) -> Result<(), DeflateError> {
+        let bits = &mut self.bits;
+        let next_state = match &mut self.state {
+            State::BlockHeader => {
+                let block_header = read_block_header(bits)?;
+                let is_final = block_header.is_final;
+                match block_header.compression_type {
+                    CompressionType::NoCompression => {
+                        bits.skip_to_start_of_byte();
+                        let len = bits.read_u16_le()?;
+                        let nlen = bits.read_u16_le()?;
+                        if len != !nlen {
+                            return Err(DeflateError::LengthMismatch { len, nlen });
+                        }
+                        State::Stored {
+                            remaining: len as usize,
+                            is_final,
+                        }
+                    }
+                    CompressionType::FixedHuffman => State::Huffman {
+                        alphabets: Alphabets::Fixed,
+                        is_final,
+                    },
+                    CompressionType::DynamicHuffman => State::Huffman {
+                        alphabets: Alphabets::Dynamic(Box::new(huffman::read_dynamic_alphabets(
+                            bits,
+                        )?)),
+                        is_final,
+                    },
+                    CompressionType::Reserved => return Err(DeflateError::ReservedBlockType),
+                }
+            }
+            State::Stored {
+                remaining,
+                is_final,
+            } => {
+                if *remaining == 0 {
+                    Self::end_of_block(*is_final)
+                } else {
+                    for _ in 0..cmp::min(*remaining, STORED_CHUNK_SIZE) {
+                        self.buf.push(bits.read_next_byte()?);
+                        *remaining -= 1;
+                    }
+                    return Ok(());
+                }
+            }
+            State::Huffman {
+                alphabets,
+                is_final,
+            } => {
+                let (literal_alphabet, distance_alphabet) = alphabets.get();
+                match huffman::read_deflate_symbol(bits, literal_alphabet, distance_alphabet)? {
+                    DeflateSymbol::Literal(value) => {
+                        self.buf.push(value);
+                        return Ok(());
+                    }
+                    DeflateSymbol::LengthAndDistance(length, distance) => {
+                        let current_idx = self.buf.len();
+                        huffman::check_distance(distance, current_idx)?;
+                        let copy_start = current_idx - distance as usize;
+                        for idx in copy_start..copy_start + length as usize {
+                            self.buf.push(self.buf[idx]);
+                        }
+                        return Ok(());
+                    }
+                    DeflateSymbol::EndOfData => Self::end_of_block(*is_final),
+                }
+            }
+            State::Done => return Ok(()),
+        };
+        self.state = next_state;
+        Ok(())
+    }
+
+    // Drop bytes that have been read and are too far back to be referred to
+    fn trim_buffer(&mut self) {
+        let keep_from = cmp::min(self.read_pos, self.buf.len().saturating_sub(WINDOW_SIZE));
+        if keep_from >= WINDOW_SIZE {
+            self.buf.drain(..keep_from);
+            self.read_pos -= keep_from;
+        }
+    }
+}
+
+impl<R: Read> Read for DeflateReader<R> {
+    fn read(&mut self, out: &mut [u8]) -> io::Result<usize> {
+        while self.buf.len() - self.read_pos < out.len() && !self.is_done() {
+            self.step()?;
+        }
+        let n = cmp::min(out.len(), self.buf.len() - self.read_pos);
+        out[..n].copy_from_slice(&self.buf[self.read_pos..self.read_pos + n]);
+        self.read_pos += n;
+        self.trim_buffer();
+        Ok(n)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::deflate::{compress_fixed, compress_stored, decompress_blocks};
+
+    fn read_in_chunks<R: Read>(mut reader: R, chunk_size: usize) -> Vec<u8> {
+        let mut out = Vec::new();
+        let mut chunk = vec![0; chunk_size];
+        loop {
+            let n = reader.read(&mut chunk).unwrap();
+            if n == 0 {
+                return out;
+            }
+            out.extend_from_slice(&chunk[..n]);
+        }
+    }
+
+    #[test]
+    fn test_deflate_reader() {
+        let input: Vec<u8> = (0u32..150_000)
+            .map(|i| b"ACGT"[((i.wrapping_mul(2654435761) >> 13) % 4) as usize])
+            .collect();
+        let mut fixed = Vec::new();
+        compress_fixed(&input, &mut fixed).unwrap();
+        let mut stored = Vec::new();
+        compress_stored(&input, &mut stored);
+
+        for compressed in &[fixed, stored] {
+            let mut expected = Vec::new();
+            decompress_blocks(compressed, &mut expected).unwrap();
+            for &chunk_size in &[1, 7, 4096, 200_000] {
+                let out = read_in_chunks(DeflateReader::new(&compressed[..]), chunk_size);
+                assert!(out == expected, "chunk size {}", chunk_size);
+            }
+        }
+    }
+}
@@ -1,2 +1,2 @@
+pub mod deflate;
 pub mod zlib;
-pub mod deflate;
\ No newline at end of file
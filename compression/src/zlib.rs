@@ -1,9 +1,84 @@
-use crate::deflate;
-use anyhow::{bail, Result};
+use crate::deflate::{self, DeflateError, DeflateReader, Window};
+use digest::{Adler32, Digest};
+use fiddling::BitStream;
+use std::io::{self, Read};
+use std::{error, fmt};
+
+/// The ways decompressing a zlib stream can fail
+#[derive(Debug)]
+pub enum ZlibError {
+    /// The input is too short to contain the zlib header or the ADLER32 trailer
+    Truncated,
+    /// The FCHECK bits of the header don't check out
+    HeaderCheck,
+    /// The CM and CINFO bits of the header aren't those of deflate with a valid window size
+    UnknownCompressionMethod {
+        cmf: u8,
+    },
+    /// The stream has the FDICT flag set, but no dictionary was given
+    DictionaryRequired {
+        dict_id: u32,
+    },
+    /// The DICTID of the stream isn't the Adler-32 of the given dictionary
+    DictionaryMismatch {
+        dict_id: u32,
+        dict_adler: u32,
+    },
+    /// The ADLER32 trailer doesn't match the decompressed data
+    ChecksumMismatch {
+        expected: u32,
+        actual: u32,
+    },
+    Deflate(DeflateError),
+}
+
+impl fmt::Display for ZlibError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ZlibError::Truncated => write!(f, "Unexpected end of zlib stream"),
+            ZlibError::HeaderCheck => write!(f, "FCHECK failed"),
+            ZlibError::UnknownCompressionMethod { cmf } => {
+                write!(f, "Unknown compression method {:02x}", cmf)
+            }
+            ZlibError::DictionaryRequired { dict_id } => {
+                write!(f, "Preset dictionary {:08x} required", dict_id)
+            }
+            ZlibError::DictionaryMismatch {
+                dict_id,
+                dict_adler,
+            } => write!(
+                f,
+                "Preset dictionary {:08x} doesn't match the given dictionary {:08x}",
+                dict_id, dict_adler
+            ),
+            ZlibError::ChecksumMismatch { expected, actual } => write!(
+                f,
+                "Adler-32 {:08x} doesn't match the expected {:08x}",
+                actual, expected
+            ),
+            ZlibError::Deflate(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl error::Error for ZlibError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            ZlibError::Deflate(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<DeflateError> for ZlibError {
+    fn from(e: DeflateError) -> Self {
+        ZlibError::Deflate(e)
+    }
+}
 
 #[derive(PartialEq, Debug)]
 enum CompressionMethod {
-    Deflate { window_size: u16 },
+    Deflate { window_size: u32 },
     Unknown,
 }
 
@@ -13,9 +88,10 @@ impl From<u8> for CompressionMethod {
         let cm = b & 0b00001111; // First 4 bits
         let cinfo = b >> 4; // Last 4 bits
 
-        if cm == 8 {
+        // CINFO above 7, i.e. a window larger than 32K, is not allowed by the spec
+        if cm == 8 && cinfo <= 7 {
             // TODO: see http://optipng.sourceforge.net/pngtech/zlib-spec-correction.html
-            let window_size = u16::pow(2u16, cinfo as u32 + 8);
+            let window_size = 1u32 << (cinfo + 8);
             Deflate { window_size }
         } else {
             Unknown
@@ -40,7 +116,7 @@ struct Flags {
 impl From<u8> for Flags {
     fn from(b: u8) -> Self {
         use CompressionLevel::*;
-        let preset_dictionary = (b & 0b0001_0000) == 0b0001_0000;
+        let preset_dictionary = (b & 0b0010_0000) == 0b0010_0000;
         let flevel = b >> 6;
         let compression_level = match flevel {
             0 => Level1,
@@ -60,16 +136,391 @@ fn check_cmf_flg(cmf: u8, flg: u8) -> bool {
     (256 * cmf as u32 + flg as u32) % 31 == 0
 }
 
-pub fn decompress(in_bytes: &[u8], out_buf: &mut Vec<u8>) -> Result<()> {
-    let compression_method = CompressionMethod::from(in_bytes[0]);
-    println!("{:?}", compression_method);
-    let flags = Flags::from(in_bytes[1]);
-    println!("{:?}", flags);
-    if !check_cmf_flg(in_bytes[0], in_bytes[1]) {
-        bail!("FCHECK failed");
+fn check_header(cmf: u8, flg: u8) -> Result<(), ZlibError> {
+    if !check_cmf_flg(cmf, flg) {
+        return Err(ZlibError::HeaderCheck);
+    }
+    if CompressionMethod::from(cmf) == CompressionMethod::Unknown {
+        return Err(ZlibError::UnknownCompressionMethod { cmf });
     }
+    Ok(())
+}
 
-    deflate::decompress_blocks(&in_bytes[2..], out_buf)?;
+pub fn decompress(in_bytes: &[u8], out_buf: &mut Vec<u8>) -> Result<(), ZlibError> {
+    decompress_with_optional_dict(in_bytes, None, out_buf)
+}
+
+/// Decompress a zlib stream that may use the preset dictionary `dict`. If the stream has the
+/// FDICT flag set, its DICTID must match the Adler-32 of `dict`, and the deflate data can then
+/// refer back into `dict`. Streams without FDICT ignore `dict`.
+pub fn decompress_with_dict(
+    in_bytes: &[u8],
+    dict: &[u8],
+    out_buf: &mut Vec<u8>,
+) -> Result<(), ZlibError> {
+    decompress_with_optional_dict(in_bytes, Some(dict), out_buf)
+}
+
+fn decompress_with_optional_dict(
+    in_bytes: &[u8],
+    dict: Option<&[u8]>,
+    out_buf: &mut Vec<u8>,
+) -> Result<(), ZlibError> {
+    if in_bytes.len() < 2 {
+        return Err(ZlibError::Truncated);
+    }
+    let flags = Flags::from(in_bytes[1]);
+    check_header(in_bytes[0], in_bytes[1])?;
 
+    let out_start = out_buf.len();
+    let deflate_start = if flags.preset_dictionary { 6 } else { 2 };
+    let deflate_len = if !flags.preset_dictionary {
+        deflate::decompress_blocks_until(&in_bytes[2..], out_buf, usize::MAX, false)?
+    } else {
+        if in_bytes.len() < 6 {
+            return Err(ZlibError::Truncated);
+        }
+        let dict_id = u32::from_be_bytes([in_bytes[2], in_bytes[3], in_bytes[4], in_bytes[5]]);
+        let dict = dict.ok_or(ZlibError::DictionaryRequired { dict_id })?;
+        let mut adler = Adler32::new();
+        adler.update_all(dict);
+        let dict_adler = adler.digest();
+        if dict_id != dict_adler {
+            return Err(ZlibError::DictionaryMismatch {
+                dict_id,
+                dict_adler,
+            });
+        }
+
+        let mut window = Window::from_bytes(dict);
+        deflate::decompress_with_window_until(&in_bytes[6..], &mut window, out_buf, false)?
+    };
+
+    let trailer_start = deflate_start + deflate_len;
+    let trailer = in_bytes
+        .get(trailer_start..trailer_start + 4)
+        .ok_or(ZlibError::Truncated)?;
+    let expected = u32::from_be_bytes([trailer[0], trailer[1], trailer[2], trailer[3]]);
+    let mut adler = Adler32::new();
+    adler.update_all(&out_buf[out_start..]);
+    let actual = adler.digest();
+    if expected != actual {
+        return Err(ZlibError::ChecksumMismatch { expected, actual });
+    }
     Ok(())
 }
+
+/// A streaming zlib decompressor, reading compressed bytes from `inner` as needed. The Adler-32
+/// checksum is verified once the end of the stream is reached. Preset dictionaries are not
+/// supported.
+///
+/// # Examples
+///
+/// ```rust
+/// use compression::zlib::ZlibReader;
+/// use std::io::Read;
+///
+/// // "Hello, stored block!" as a single stored block
+/// let compressed = [
+///     0x78, 0x01, 0x01, 0x14, 0x00, 0xeb, 0xff, 0x48, 0x65, 0x6c, 0x6c, 0x6f, 0x2c, 0x20, 0x73,
+///     0x74, 0x6f, 0x72, 0x65, 0x64, 0x20, 0x62, 0x6c, 0x6f, 0x63, 0x6b, 0x21, 0x4b, 0x8c, 0x07,
+///     0x1e,
+/// ];
+/// let mut reader = ZlibReader::new(&compressed[..]);
+/// let mut out = String::new();
+/// reader.read_to_string(&mut out).unwrap();
+/// assert_eq!(out, "Hello, stored block!");
+/// ```
+pub struct ZlibReader<R> {
+    deflate: DeflateReader<R>,
+    adler: Adler32,
+    header_read: bool,
+    checksum_verified: bool,
+}
+
+impl<R: Read> ZlibReader<R> {
+    pub fn new(inner: R) -> ZlibReader<R> {
+        ZlibReader {
+            deflate: DeflateReader::from_bit_stream(BitStream::new(inner)),
+            adler: Adler32::new(),
+            header_read: false,
+            checksum_verified: false,
+        }
+    }
+
+    fn read_header(&mut self) -> io::Result<()> {
+        let bits = self.deflate.bits_mut();
+        let cmf = bits.read_next_byte()?;
+        let flg = bits.read_next_byte()?;
+        if let Err(e) = check_header(cmf, flg) {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, e));
+        }
+        if Flags::from(flg).preset_dictionary {
+            let mut dict_id = [0; 4];
+            for byte in &mut dict_id {
+                *byte = bits.read_next_byte()?;
+            }
+            let dict_id = u32::from_be_bytes(dict_id);
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                ZlibError::DictionaryRequired { dict_id },
+            ));
+        }
+        self.header_read = true;
+        Ok(())
+    }
+
+    fn verify_checksum(&mut self) -> io::Result<()> {
+        let bits = self.deflate.bits_mut();
+        let mut expected = [0; 4];
+        for byte in &mut expected {
+            *byte = bits.read_next_byte()?;
+        }
+        let expected = u32::from_be_bytes(expected);
+        let actual = self.adler.digest();
+        if expected != actual {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                ZlibError::ChecksumMismatch { expected, actual },
+            ));
+        }
+        self.checksum_verified = true;
+        Ok(())
+    }
+}
+
+impl<R: Read> Read for ZlibReader<R> {
+    fn read(&mut self, out: &mut [u8]) -> io::Result<usize> {
+        if !self.header_read {
+            self.read_header()?;
+        }
+        let n = self.deflate.read(out)?;
+        self.adler.update_all(&out[..n]);
+        if n == 0 && !out.is_empty() && self.deflate.is_done() && !self.checksum_verified {
+            self.verify_checksum()?;
+        }
+        Ok(n)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compression_method() {
+        assert_eq!(
+            CompressionMethod::from(0x78),
+            CompressionMethod::Deflate { window_size: 32768 }
+        );
+        assert_eq!(
+            CompressionMethod::from(0x08),
+            CompressionMethod::Deflate { window_size: 256 }
+        );
+        assert_eq!(CompressionMethod::from(0x88), CompressionMethod::Unknown);
+        assert_eq!(CompressionMethod::from(0xf8), CompressionMethod::Unknown);
+        assert_eq!(CompressionMethod::from(0x79), CompressionMethod::Unknown);
+    }
+
+    #[test]
+    fn test_decompress_errors() {
+        let mut out = Vec::new();
+        assert!(matches!(
+            decompress(&[0x78], &mut out),
+            Err(ZlibError::Truncated)
+        ));
+        assert!(matches!(
+            decompress(&[0x78, 0x9d, 0x03, 0x00], &mut out),
+            Err(ZlibError::HeaderCheck)
+        ));
+        assert!(matches!(
+            decompress(&[0x88, 0x1c, 0x03, 0x00], &mut out),
+            Err(ZlibError::UnknownCompressionMethod { cmf: 0x88 })
+        ));
+        assert!(matches!(
+            decompress(&[0x78, 0x9c, 0b0000_0111], &mut out),
+            Err(ZlibError::Deflate(DeflateError::ReservedBlockType))
+        ));
+    }
+
+    // Golden data compressed with the reference zlib implementation
+
+    // Level 0, a single stored block
+    const STORED: [u8; 31] = [
+        0x78, 0x01, 0x01, 0x14, 0x00, 0xeb, 0xff, 0x48, 0x65, 0x6c, 0x6c, 0x6f, 0x2c, 0x20, 0x73,
+        0x74, 0x6f, 0x72, 0x65, 0x64, 0x20, 0x62, 0x6c, 0x6f, 0x63, 0x6b, 0x21, 0x4b, 0x8c, 0x07,
+        0x1e,
+    ];
+    const STORED_ORIGINAL: &[u8] = b"Hello, stored block!";
+
+    // Z_FIXED strategy, a fixed Huffman block
+    const FIXED: [u8; 37] = [
+        0x78, 0x01, 0x4b, 0x4c, 0x4a, 0x4e, 0x84, 0x21, 0x85, 0xb4, 0xcc, 0x8a, 0xd4, 0x14, 0x05,
+        0x8f, 0xd2, 0xb4, 0xb4, 0xdc, 0xc4, 0x3c, 0x85, 0xe4, 0xfc, 0x94, 0xd4, 0x62, 0x1d, 0x6c,
+        0x82, 0x8a, 0x00, 0x1d, 0x20, 0x13, 0x6c,
+    ];
+    const FIXED_ORIGINAL: &[u8] = b"abcabcabcabc fixed Huffman codes, fixed Huffman codes!";
+
+    // Level 9, a dynamic Huffman block of DYNAMIC_ORIGINAL repeated four times
+    const DYNAMIC: [u8; 115] = [
+        0x78, 0xda, 0xed, 0x8d, 0xbb, 0x15, 0xc2, 0x30, 0x10, 0x04, 0x5b, 0x59, 0x1a, 0x70, 0x1d,
+        0x84, 0x04, 0x6e, 0x40, 0xc2, 0x27, 0x59, 0x20, 0xeb, 0xb0, 0xbe, 0x96, 0xaa, 0xe7, 0x1e,
+        0xcf, 0x35, 0x38, 0x22, 0x9e, 0xd9, 0x9d, 0x79, 0x25, 0xec, 0xc5, 0x3d, 0xdf, 0xd0, 0x91,
+        0x5b, 0x80, 0xe1, 0x03, 0xaf, 0xb2, 0x7d, 0x12, 0xb8, 0x52, 0x44, 0x16, 0xec, 0xd5, 0xe8,
+        0x58, 0xd8, 0x4e, 0x78, 0x28, 0xf1, 0xb6, 0x0e, 0x2d, 0x52, 0x73, 0x79, 0x85, 0x71, 0x95,
+        0x04, 0x0d, 0x0a, 0xf0, 0x6e, 0x2f, 0x1c, 0x65, 0x6b, 0xd3, 0x84, 0x3b, 0x37, 0x54, 0x3a,
+        0x5c, 0xb0, 0xbe, 0x9f, 0xf7, 0x8b, 0x32, 0x19, 0x83, 0x74, 0x54, 0xe9, 0x17, 0xb8, 0x61,
+        0xfe, 0xa7, 0xaf, 0x4d, 0x7f, 0x01, 0x18, 0x0d, 0xb1, 0x89,
+    ];
+    const DYNAMIC_ORIGINAL: &[u8] = b"The quick brown fox jumps over the lazy dog. \
+        Pack my box with five dozen liquor jugs. How vexingly quick daft zebras jump! ";
+
+    // Level 9, `window_original()`
+    const WINDOW: &[u8] = include_bytes!("../testdata/window.zz");
+
+    // Three copies of 31000 pseudorandom bases, so that copying them needs back-references
+    // almost all the way through the 32 KiB window
+    fn window_original() -> Vec<u8> {
+        let mut x: u32 = 1;
+        let bases: Vec<u8> = (0..31000)
+            .map(|_| {
+                x = x.wrapping_mul(1103515245).wrapping_add(12345) % (1 << 31);
+                b"ACGT"[((x >> 16) & 3) as usize]
+            })
+            .collect();
+        bases.repeat(3)
+    }
+
+    fn assert_decompresses_to(compressed: &[u8], original: &[u8]) {
+        let mut out = Vec::new();
+        decompress(compressed, &mut out).unwrap();
+        assert_eq!(out.len(), original.len());
+        assert!(
+            out == original,
+            "decompressed bytes differ from the original"
+        );
+    }
+
+    #[test]
+    fn test_golden_stored() {
+        assert_decompresses_to(&STORED, STORED_ORIGINAL);
+    }
+
+    #[test]
+    fn test_golden_fixed_huffman() {
+        assert_decompresses_to(&FIXED, FIXED_ORIGINAL);
+    }
+
+    #[test]
+    fn test_golden_dynamic_huffman() {
+        assert_decompresses_to(&DYNAMIC, &DYNAMIC_ORIGINAL.repeat(4));
+    }
+
+    #[test]
+    fn test_golden_window() {
+        assert_decompresses_to(WINDOW, &window_original());
+    }
+
+    // Level 9 with the preset dictionary DICT
+    const WITH_DICT: [u8; 23] = [
+        0x78, 0xf9, 0x44, 0x7e, 0x06, 0xb8, 0xf3, 0xc0, 0x21, 0xa4, 0x90, 0x98, 0x9e, 0x98, 0x99,
+        0x87, 0x22, 0x01, 0x00, 0x51, 0x7d, 0x0f, 0x6f,
+    ];
+    const DICT: &[u8] = b"Hello, dictionary! ";
+    const WITH_DICT_ORIGINAL: &[u8] = b"Hello, dictionary! Hello again, dictionary!";
+
+    #[test]
+    fn test_decompress_checksum() {
+        for compressed in &[&STORED[..], &FIXED[..], &DYNAMIC[..], &WITH_DICT[..]] {
+            let last = compressed.len() - 1;
+            let mut corrupt_checksum = compressed.to_vec();
+            corrupt_checksum[last] ^= 1;
+            let mut out = Vec::new();
+            match decompress_with_dict(&corrupt_checksum, DICT, &mut out) {
+                Err(ZlibError::ChecksumMismatch { expected, actual }) => {
+                    assert_eq!(expected ^ 1, actual)
+                }
+                result => panic!("expected a checksum mismatch, got {:?}", result),
+            }
+
+            let mut out = Vec::new();
+            assert!(matches!(
+                decompress_with_dict(&compressed[..last], DICT, &mut out),
+                Err(ZlibError::Truncated)
+            ));
+        }
+
+        // Only the data decompressed by this call is checksummed
+        let mut out = b"Already here".to_vec();
+        decompress(&FIXED, &mut out).unwrap();
+        assert_eq!(&out[12..], FIXED_ORIGINAL);
+    }
+
+    #[test]
+    fn test_decompress_with_dict() {
+        let mut out = Vec::new();
+        decompress_with_dict(&WITH_DICT, DICT, &mut out).unwrap();
+        assert_eq!(out, WITH_DICT_ORIGINAL);
+
+        let mut out = Vec::new();
+        assert!(matches!(
+            decompress(&WITH_DICT, &mut out),
+            Err(ZlibError::DictionaryRequired {
+                dict_id: 0x447e06b8
+            })
+        ));
+        assert!(matches!(
+            decompress_with_dict(&WITH_DICT, b"Some other dictionary", &mut out),
+            Err(ZlibError::DictionaryMismatch {
+                dict_id: 0x447e06b8,
+                ..
+            })
+        ));
+        assert!(matches!(
+            decompress_with_dict(&WITH_DICT[..5], DICT, &mut out),
+            Err(ZlibError::Truncated)
+        ));
+
+        // Without FDICT the dictionary isn't used
+        let mut out = Vec::new();
+        decompress_with_dict(&STORED, DICT, &mut out).unwrap();
+        assert_eq!(out, STORED_ORIGINAL);
+    }
+
+    fn read_in_chunks<R: Read>(mut reader: R, chunk_size: usize) -> io::Result<Vec<u8>> {
+        let mut out = Vec::new();
+        let mut chunk = vec![0; chunk_size];
+        loop {
+            let n = reader.read(&mut chunk)?;
+            if n == 0 {
+                return Ok(out);
+            }
+            out.extend_from_slice(&chunk[..n]);
+        }
+    }
+
+    #[test]
+    fn test_zlib_reader() {
+        for compressed in &[&STORED[..], &FIXED[..], &DYNAMIC[..], WINDOW] {
+            let mut expected = Vec::new();
+            decompress(compressed, &mut expected).unwrap();
+            let out = read_in_chunks(ZlibReader::new(*compressed), 7).unwrap();
+            assert_eq!(out.len(), expected.len());
+            assert!(out == expected);
+        }
+    }
+
+    #[test]
+    fn test_zlib_reader_errors() {
+        let mut corrupt_checksum = STORED;
+        corrupt_checksum[30] ^= 1;
+        let e = read_in_chunks(ZlibReader::new(&corrupt_checksum[..]), 7).unwrap_err();
+        assert!(e.to_string().contains("Adler-32"), "{}", e);
+
+        let e = read_in_chunks(ZlibReader::new(&WITH_DICT[..]), 7).unwrap_err();
+        assert!(e.to_string().contains("dictionary"), "{}", e);
+
+        let e = read_in_chunks(ZlibReader::new(&STORED[..20]), 7).unwrap_err();
+        assert_eq!(e.kind(), io::ErrorKind::UnexpectedEof);
+    }
+}
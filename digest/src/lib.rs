@@ -1,13 +1,54 @@
 use std::io;
-use std::io::Read;
+use std::io::{Read, Write};
 
 //
 // Digest trait
 //
 
+/// A checksum or hash computed byte by byte
+///
+/// # Examples
+///
+/// ```rust
+/// use digest::Digest;
+///
+/// struct Xor8(u8);
+///
+/// impl Digest for Xor8 {
+///     type Output = u8;
+///
+///     fn update(&mut self, b: u8) {
+///         self.0 ^= b;
+///     }
+///
+///     fn digest(&self) -> u8 {
+///         self.0
+///     }
+///
+///     fn reset(&mut self) {
+///         self.0 = 0;
+///     }
+/// }
+///
+/// let mut xor = Xor8(0);
+/// xor.update_all(&[0b1100, 0b1010]);
+/// assert_eq!(xor.digest(), 0b0110);
+/// ```
 pub trait Digest {
+    /// The type of the digest value, e.g. `u32` for 32-bit checksums
+    type Output;
+
     fn update(&mut self, b: u8);
-    fn digest(&self) -> u32;
+
+    /// Update with all of `bytes`. Implementations can override this with something faster than
+    /// updating byte by byte.
+    fn update_all(&mut self, bytes: &[u8]) {
+        for b in bytes {
+            self.update(*b)
+        }
+    }
+
+    fn digest(&self) -> Self::Output;
     fn reset(&mut self);
 }
 
@@ -15,7 +56,9 @@ pub trait Digest {
 // CRC-32
 //
 
-const fn make_crc_table() -> [u32; 256] {
+const CRC_POLYNOMIAL: u32 = 0xedb88320;
+
+const fn make_crc_table(polynomial: u32) -> [u32; 256] {
     let mut n: usize = 0;
     let mut crc_table: [u32; 256] = [0; 256];
     while n < 256 {
@@ -23,7 +66,7 @@ const fn make_crc_table() -> [u32; 256] {
         let mut k = 0;
         while k < 8 {
             if c & 1 != 0 {
-                c = 0xedb88320 ^ (c >> 1)
+                c = polynomial ^ (c >> 1)
             } else {
                 c = c >> 1;
             }
@@ -35,10 +78,54 @@ const fn make_crc_table() -> [u32; 256] {
     crc_table
 }
 
-const CRC_TABLE: [u32; 256] = make_crc_table();
+/// Tables for slice-by-8: `tables[k][n]` is the CRC of byte `n` followed by `k` zero bytes
+const fn make_crc_tables(polynomial: u32) -> [[u32; 256]; 8] {
+    let mut crc_tables: [[u32; 256]; 8] = [[0; 256]; 8];
+    crc_tables[0] = make_crc_table(polynomial);
+    let mut k = 1;
+    while k < 8 {
+        let mut n = 0;
+        while n < 256 {
+            let c = crc_tables[k - 1][n];
+            crc_tables[k][n] = (c >> 8) ^ crc_tables[0][(c & 0xff) as usize];
+            n += 1;
+        }
+        k += 1;
+    }
+    crc_tables
+}
+
+const CRC_TABLES: [[u32; 256]; 8] = make_crc_tables(CRC_POLYNOMIAL);
+
+fn crc_update(table: &[u32; 256], crc: u32, b: u8) -> u32 {
+    table[((crc ^ b as u32) & 0xff) as usize] ^ (crc >> 8)
+}
+
+/// Slice-by-8, i.e. process eight bytes at a time with one lookup per byte
+fn crc_update_all(tables: &[[u32; 256]; 8], mut crc: u32, bytes: &[u8]) -> u32 {
+    let t = tables;
+    let mut chunks = bytes.chunks_exact(8);
+    for chunk in &mut chunks {
+        let lo = crc ^ u32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]);
+        let hi = u32::from_le_bytes([chunk[4], chunk[5], chunk[6], chunk[7]]);
+        crc = t[7][(lo & 0xff) as usize]
+            ^ t[6][((lo >> 8) & 0xff) as usize]
+            ^ t[5][((lo >> 16) & 0xff) as usize]
+            ^ t[4][(lo >> 24) as usize]
+            ^ t[3][(hi & 0xff) as usize]
+            ^ t[2][((hi >> 8) & 0xff) as usize]
+            ^ t[1][((hi >> 16) & 0xff) as usize]
+            ^ t[0][(hi >> 24) as usize];
+    }
+    for b in chunks.remainder() {
+        crc = crc_update(&t[0], crc, *b);
+    }
+    crc
+}
 
 pub struct Crc32 {
     crc: u32,
+    tables: Option<Box<[[u32; 256]; 8]>>, // None for the default polynomial
 }
 
 /// # Examples
@@ -58,13 +145,163 @@ pub struct Crc32 {
 /// ```
 impl Crc32 {
     pub fn new() -> Crc32 {
-        Crc32 { crc: 0xffffffff }
+        Crc32 {
+            crc: 0xffffffff,
+            tables: None,
+        }
+    }
+
+    /// A CRC-32 with a custom `polynomial` instead of the default 0xedb88320, in the same
+    /// reversed bit order. The tables are computed here, so construction is much more expensive
+    /// than with `new`. Digests are only comparable with ones computed using the same polynomial,
+    /// e.g. `combine` assumes the default one.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use digest::{Crc32, Digest};
+    ///
+    /// let mut crc = Crc32::with_polynomial(0x82f63b78);
+    /// crc.update_all(b"123456789");
+    /// assert_eq!(crc.digest(), 0xe3069283);
+    ///
+    /// let mut default = Crc32::with_polynomial(0xedb88320);
+    /// default.update_all(b"123456789");
+    /// assert_eq!(default.digest(), 0xcbf43926);
+    /// assert_eq!(default.table(), Crc32::new().table());
+    /// ```
+    pub fn with_polynomial(polynomial: u32) -> Crc32 {
+        Crc32 {
+            crc: 0xffffffff,
+            tables: Some(Box::new(make_crc_tables(polynomial))),
+        }
+    }
+
+    /// The lookup table for updating the CRC one byte at a time
+    pub fn table(&self) -> &[u32; 256] {
+        &self.tables()[0]
+    }
+
+    fn tables(&self) -> &[[u32; 256]; 8] {
+        self.tables.as_deref().unwrap_or(&CRC_TABLES)
+    }
+
+    /// Resume from a completed `digest()` value, so that further updates continue as if the
+    /// digest had never stopped. This only round-trips the value returned by `digest`, not the
+    /// internal state at any other point.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use digest::{Crc32, Digest};
+    ///
+    /// let data = b"The quick brown fox jumps over the lazy dog";
+    /// let mut crc = Crc32::new();
+    /// crc.update_all(data);
+    ///
+    /// let mut paused = Crc32::new();
+    /// paused.update_all(&data[..10]);
+    /// let saved = paused.digest();
+    ///
+    /// let mut resumed = Crc32::from_digest(saved);
+    /// resumed.update_all(&data[10..]);
+    /// assert_eq!(resumed.digest(), crc.digest());
+    /// ```
+    pub fn from_digest(value: u32) -> Crc32 {
+        Crc32 {
+            crc: value ^ 0xffffffff,
+            tables: None,
+        }
+    }
+
+    /// Combine the digests of two consecutive byte sequences A and B into the digest of their
+    /// concatenation, given only the length of B. Same as `crc32_combine` in zlib.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use digest::{Crc32, Digest};
+    ///
+    /// let crc_of = |bytes: &[u8]| {
+    ///     let mut crc = Crc32::new();
+    ///     bytes.iter().for_each(|b| crc.update(*b));
+    ///     crc.digest()
+    /// };
+    ///
+    /// let data = b"The quick brown fox jumps over the lazy dog";
+    /// let (a, b) = data.split_at(10);
+    /// assert_eq!(Crc32::combine(crc_of(a), crc_of(b), b.len()), crc_of(data));
+    /// assert_eq!(Crc32::combine(crc_of(data), crc_of(&[]), 0), crc_of(data));
+    /// ```
+    pub fn combine(crc_a: u32, crc_b: u32, len_b: usize) -> u32 {
+        if len_b == 0 {
+            return crc_a;
+        }
+
+        // The operator for one zero bit, and then for two and four zero bits by squaring
+        let mut odd = [0u32; 32];
+        let mut even = [0u32; 32];
+        odd[0] = CRC_POLYNOMIAL;
+        for (n, row) in odd.iter_mut().enumerate().skip(1) {
+            *row = 1 << (n - 1);
+        }
+        gf2_matrix_square(&mut even, &odd);
+        gf2_matrix_square(&mut odd, &even);
+
+        // Apply len_b zero bytes to crc_a, squaring the operator for each bit of len_b
+        let mut crc = crc_a;
+        let mut len = len_b;
+        loop {
+            gf2_matrix_square(&mut even, &odd);
+            if len & 1 != 0 {
+                crc = gf2_matrix_times(&even, crc);
+            }
+            len >>= 1;
+            if len == 0 {
+                break;
+            }
+
+            gf2_matrix_square(&mut odd, &even);
+            if len & 1 != 0 {
+                crc = gf2_matrix_times(&odd, crc);
+            }
+            len >>= 1;
+            if len == 0 {
+                break;
+            }
+        }
+        crc ^ crc_b
+    }
+}
+
+fn gf2_matrix_times(mat: &[u32; 32], mut vec: u32) -> u32 {
+    let mut sum = 0;
+    let mut i = 0;
+    while vec != 0 {
+        if vec & 1 != 0 {
+            sum ^= mat[i];
+        }
+        vec >>= 1;
+        i += 1;
+    }
+    sum
+}
+
+fn gf2_matrix_square(square: &mut [u32; 32], mat: &[u32; 32]) {
+    for (row, &mat_row) in square.iter_mut().zip(mat.iter()) {
+        *row = gf2_matrix_times(mat, mat_row);
     }
 }
 
 impl Digest for Crc32 {
+    type Output = u32;
+
     fn update(&mut self, b: u8) {
-        self.crc = CRC_TABLE[((self.crc ^ b as u32) & 0xff) as usize] ^ (self.crc >> 8);
+        self.crc = crc_update(self.table(), self.crc, b);
+    }
+
+    fn update_all(&mut self, bytes: &[u8]) {
+        self.crc = crc_update_all(self.tables(), self.crc, bytes);
     }
 
     fn digest(&self) -> u32 {
@@ -79,7 +316,7 @@ impl Digest for Crc32 {
 /// # Examples
 ///
 /// ```rust
-/// use std::io::Read;
+/// use std::io::{Read, Write};
 /// use digest::{DigestReader, Crc32};
 ///
 /// let input = vec![0x49 as u8, 0x48, 0x44, 0x52, 0x00, 0x00, 0x03, 0x20, 0x00, 0x00, 0x02, 0x58,
@@ -103,7 +340,7 @@ impl<R: Read, D: Digest> DigestReader<R, D> {
         DigestReader { inner, digest }
     }
 
-    pub fn digest(&self) -> u32 {
+    pub fn digest(&self) -> D::Output {
         self.digest.digest()
     }
 
@@ -129,20 +366,84 @@ impl<R, D> DigestReader<R, D> {
 impl<R: Read, D: Digest> Read for DigestReader<R, D> {
     fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
         let n_read = self.inner.read(buf)?;
-        for b in buf.iter().take(n_read) {
-            self.digest.update(*b);
-        }
+        self.digest.update_all(&buf[..n_read]);
         Ok(n_read)
     }
 }
 
+/// # Examples
+///
+/// ```rust
+/// use std::io::Write;
+/// use digest::{DigestWriter, Crc32};
+///
+/// let input = [0x49 as u8, 0x48, 0x44, 0x52, 0x00, 0x00, 0x03, 0x20, 0x00, 0x00, 0x02, 0x58,
+/// 0x08, 0x06, 0x00, 0x00, 0x00];
+/// let mut writer = DigestWriter::new(Vec::new(), Crc32::new());
+///
+/// writer.write_all(&input).unwrap();
+///
+/// assert_eq!(writer.digest(), 2591457904);
+/// assert_eq!(writer.into_inner(), input);
+/// ```
+pub struct DigestWriter<W, D> {
+    inner: W,
+    digest: D,
+}
+
+impl<W: Write, D: Digest> DigestWriter<W, D> {
+    pub fn new(inner: W, digest: D) -> DigestWriter<W, D> {
+        DigestWriter { inner, digest }
+    }
+
+    pub fn digest(&self) -> D::Output {
+        self.digest.digest()
+    }
+
+    pub fn reset_digest(&mut self) {
+        self.digest.reset();
+    }
+}
+
+impl<W, D> DigestWriter<W, D> {
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.inner
+    }
+
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+impl<W: Write, D: Digest> Write for DigestWriter<W, D> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let n_written = self.inner.write(buf)?;
+        self.digest.update_all(&buf[..n_written]);
+        Ok(n_written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
 //
 // Adler-32
 //
 
+const ADLER_MODULUS: u32 = 65521;
+
+/// The most bytes that can be summed before `b` may overflow a `u32`, so the modulo only needs
+/// to be taken this often
+const ADLER_MAX_BLOCK: usize = 5552;
+
 pub struct Adler32 {
-    a: u16,
-    b: u16,
+    a: u32,
+    b: u32,
 }
 
 /// # Examples
@@ -164,16 +465,60 @@ impl Adler32 {
     pub fn new() -> Adler32 {
         Adler32 { a: 1, b: 0 }
     }
+
+    /// Combine the digests of two consecutive byte sequences A and B into the digest of their
+    /// concatenation, given only the length of B. Same as `adler32_combine` in zlib.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use digest::{Adler32, Digest};
+    ///
+    /// let adler_of = |bytes: &[u8]| {
+    ///     let mut adler = Adler32::new();
+    ///     adler.update_all(bytes);
+    ///     adler.digest()
+    /// };
+    ///
+    /// let data = b"Wikipedia is a free online encyclopedia";
+    /// let (a, b) = data.split_at(9);
+    /// assert_eq!(Adler32::combine(adler_of(a), adler_of(b), b.len()), adler_of(data));
+    /// ```
+    pub fn combine(adler_a: u32, adler_b: u32, len_b: usize) -> u32 {
+        let rem = (len_b % ADLER_MODULUS as usize) as u32;
+        let a1 = adler_a & 0xffff;
+        let b1 = adler_a >> 16;
+        let a2 = adler_b & 0xffff;
+        let b2 = adler_b >> 16;
+
+        // a = a1 + a2 - 1, b = b1 + b2 + rem * a1 - rem, all modulo ADLER_MODULUS
+        let a = (a1 + a2 + ADLER_MODULUS - 1) % ADLER_MODULUS;
+        let b = (b1 + b2 + (rem * a1) % ADLER_MODULUS + ADLER_MODULUS - rem) % ADLER_MODULUS;
+        (b << 16) | a
+    }
 }
 
 impl Digest for Adler32 {
+    type Output = u32;
+
     fn update(&mut self, b: u8) {
-        self.a = self.a.wrapping_add(b as u16);
-        self.b = self.b.wrapping_add(self.a);
+        self.a = (self.a + b as u32) % ADLER_MODULUS;
+        self.b = (self.b + self.a) % ADLER_MODULUS;
+    }
+
+    fn update_all(&mut self, bytes: &[u8]) {
+        for block in bytes.chunks(ADLER_MAX_BLOCK) {
+            for b in block {
+                self.a += *b as u32;
+                self.b += self.a;
+            }
+            self.a %= ADLER_MODULUS;
+            self.b %= ADLER_MODULUS;
+        }
     }
 
     fn digest(&self) -> u32 {
-        ((self.b as u32) << 16) + self.a as u32
+        (self.b << 16) + self.a
     }
 
     fn reset(&mut self) {
@@ -181,3 +526,252 @@ impl Digest for Adler32 {
         self.b = 0;
     }
 }
+
+//
+// CRC-32C
+//
+
+const CRC32C_POLYNOMIAL: u32 = 0x82f63b78;
+
+const CRC32C_TABLES: [[u32; 256]; 8] = make_crc_tables(CRC32C_POLYNOMIAL);
+
+/// CRC-32C, using the Castagnoli polynomial instead of the one in `Crc32`
+///
+/// # Examples
+///
+/// ```rust
+/// use digest::{Crc32c, Digest};
+///
+/// let mut crc = Crc32c::new();
+/// crc.update_all(b"123456789");
+/// assert_eq!(crc.digest(), 0xe3069283);
+///
+/// crc.reset();
+/// for b in b"123456789" {
+///     crc.update(*b);
+/// }
+/// assert_eq!(crc.digest(), 0xe3069283);
+/// ```
+pub struct Crc32c {
+    crc: u32,
+}
+
+impl Crc32c {
+    pub fn new() -> Crc32c {
+        Crc32c { crc: 0xffffffff }
+    }
+}
+
+impl Default for Crc32c {
+    fn default() -> Self {
+        Crc32c::new()
+    }
+}
+
+impl Digest for Crc32c {
+    type Output = u32;
+
+    fn update(&mut self, b: u8) {
+        self.crc = crc_update(&CRC32C_TABLES[0], self.crc, b);
+    }
+
+    fn update_all(&mut self, bytes: &[u8]) {
+        self.crc = crc_update_all(&CRC32C_TABLES, self.crc, bytes);
+    }
+
+    fn digest(&self) -> u32 {
+        self.crc ^ 0xffffffff
+    }
+
+    fn reset(&mut self) {
+        self.crc = 0xffffffff;
+    }
+}
+
+//
+// Fletcher-32
+//
+
+const FLETCHER_MODULUS: u32 = 65535;
+
+/// Fletcher-32 over little endian 16-bit words. An odd trailing byte is treated as if padded
+/// with a zero byte.
+///
+/// # Examples
+///
+/// ```rust
+/// use digest::{Digest, Fletcher32};
+///
+/// let fletcher_of = |bytes: &[u8]| {
+///     let mut fletcher = Fletcher32::new();
+///     fletcher.update_all(bytes);
+///     fletcher.digest()
+/// };
+///
+/// assert_eq!(fletcher_of(b"abcde"), 0xf04fc729);
+/// assert_eq!(fletcher_of(b"abcdef"), 0x56502d2a);
+/// assert_eq!(fletcher_of(b"abcdefgh"), 0xebe19591);
+/// ```
+pub struct Fletcher32 {
+    sum1: u16,
+    sum2: u16,
+    pending: Option<u8>,
+}
+
+impl Fletcher32 {
+    pub fn new() -> Fletcher32 {
+        Fletcher32 {
+            sum1: 0,
+            sum2: 0,
+            pending: None,
+        }
+    }
+
+    fn sums_with_word(sum1: u16, sum2: u16, word: u16) -> (u16, u16) {
+        let sum1 = (sum1 as u32 + word as u32) % FLETCHER_MODULUS;
+        let sum2 = (sum2 as u32 + sum1) % FLETCHER_MODULUS;
+        (sum1 as u16, sum2 as u16)
+    }
+}
+
+impl Default for Fletcher32 {
+    fn default() -> Self {
+        Fletcher32::new()
+    }
+}
+
+impl Digest for Fletcher32 {
+    type Output = u32;
+
+    fn update(&mut self, b: u8) {
+        match self.pending.take() {
+            None => self.pending = Some(b),
+            Some(lo) => {
+                let word = u16::from_le_bytes([lo, b]);
+                let (sum1, sum2) = Fletcher32::sums_with_word(self.sum1, self.sum2, word);
+                self.sum1 = sum1;
+                self.sum2 = sum2;
+            }
+        }
+    }
+
+    fn digest(&self) -> u32 {
+        let (sum1, sum2) = match self.pending {
+            None => (self.sum1, self.sum2),
+            Some(lo) => Fletcher32::sums_with_word(self.sum1, self.sum2, lo as u16),
+        };
+        (sum2 as u32) << 16 | sum1 as u32
+    }
+
+    fn reset(&mut self) {
+        self.sum1 = 0;
+        self.sum2 = 0;
+        self.pending = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn digest_of<D: Digest>(mut digest: D, bytes: &[u8]) -> D::Output {
+        for b in bytes {
+            digest.update(*b);
+        }
+        digest.digest()
+    }
+
+    fn test_bytes(len: usize) -> Vec<u8> {
+        (0..len as u32)
+            .map(|i| (i.wrapping_mul(2654435761) >> 13) as u8)
+            .collect()
+    }
+
+    #[test]
+    fn test_crc32_combine() {
+        let bytes = test_bytes(5000);
+        for &split in &[0, 1, 2, 3, 17, 256, 4095, 4999, 5000] {
+            let (a, b) = bytes.split_at(split);
+            assert_eq!(
+                Crc32::combine(
+                    digest_of(Crc32::new(), a),
+                    digest_of(Crc32::new(), b),
+                    b.len()
+                ),
+                digest_of(Crc32::new(), &bytes),
+                "split at {}",
+                split
+            );
+        }
+    }
+
+    #[test]
+    fn test_crc32_update_all() {
+        let bytes = test_bytes(64 * 1024);
+        let mut crc = Crc32::new();
+        crc.update_all(&bytes);
+        assert_eq!(crc.digest(), digest_of(Crc32::new(), &bytes));
+
+        // Unaligned lengths and updates split at arbitrary points
+        for &len in &[0, 1, 7, 8, 9, 15, 16, 17, 1000] {
+            let mut crc = Crc32::new();
+            crc.update_all(&bytes[..len / 3]);
+            crc.update_all(&bytes[len / 3..len]);
+            assert_eq!(
+                crc.digest(),
+                digest_of(Crc32::new(), &bytes[..len]),
+                "{} bytes",
+                len
+            );
+        }
+    }
+
+    #[test]
+    fn test_adler32() {
+        // Reference values from zlib.adler32 in Python
+        let bytes = test_bytes(20000);
+        assert_eq!(digest_of(Adler32::new(), &bytes), 0x35ade9ba);
+        let mut adler = Adler32::new();
+        adler.update_all(&bytes);
+        assert_eq!(adler.digest(), 0x35ade9ba);
+
+        // The worst case for overflow within a block
+        let bytes = [0xff; 10000];
+        assert_eq!(digest_of(Adler32::new(), &bytes), 0xb623eb2b);
+        let mut adler = Adler32::new();
+        adler.update_all(&bytes[..3]);
+        adler.update_all(&bytes[3..]);
+        assert_eq!(adler.digest(), 0xb623eb2b);
+    }
+
+    #[test]
+    fn test_crc32_from_digest() {
+        let bytes = test_bytes(3000);
+        let expected = digest_of(Crc32::new(), &bytes);
+        assert_eq!(Crc32::from_digest(Crc32::new().digest()).digest(), 0);
+        for &split in &[0, 1, 8, 1234, 3000] {
+            let (a, b) = bytes.split_at(split);
+            let resumed = Crc32::from_digest(digest_of(Crc32::new(), a));
+            assert_eq!(digest_of(resumed, b), expected, "split at {}", split);
+        }
+    }
+
+    #[test]
+    fn test_adler32_combine() {
+        let bytes = test_bytes(200_000);
+        let adler_of = |bytes: &[u8]| {
+            let mut adler = Adler32::new();
+            adler.update_all(bytes);
+            adler.digest()
+        };
+        for &split in &[0, 1, 5552, 65520, 65521, 65522, 131_042, 200_000] {
+            let (a, b) = bytes.split_at(split);
+            assert_eq!(
+                Adler32::combine(adler_of(a), adler_of(b), b.len()),
+                adler_of(&bytes),
+                "split at {}",
+                split
+            );
+        }
+    }
+}
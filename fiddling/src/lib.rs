@@ -1,4 +1,4 @@
-use std::io::Read;
+use std::io::{Read, Seek, SeekFrom, Write};
 use std::{cmp, io};
 
 /// # Examples
@@ -13,6 +13,58 @@ pub fn reverse_bits(b: u8) -> u8 {
     b
 }
 
+/// # Examples
+///
+/// ```rust
+/// assert_eq!(0b11001111_00000001, fiddling::reverse_bits_u16(0b10000000_11110011));
+/// ```
+pub fn reverse_bits_u16(v: u16) -> u16 {
+    let [lo, hi] = v.to_le_bytes();
+    u16::from_le_bytes([reverse_bits(hi), reverse_bits(lo)])
+}
+
+/// # Examples
+///
+/// ```rust
+/// assert_eq!(0x8000_0003, fiddling::reverse_bits_u32(0xc000_0001));
+/// ```
+pub fn reverse_bits_u32(v: u32) -> u32 {
+    (reverse_bits_u16(v as u16) as u32) << 16 | reverse_bits_u16((v >> 16) as u16) as u32
+}
+
+/// # Examples
+///
+/// ```rust
+/// assert_eq!(0x8000_0000_0000_0003, fiddling::reverse_bits_u64(0xc000_0000_0000_0001));
+/// ```
+pub fn reverse_bits_u64(v: u64) -> u64 {
+    (reverse_bits_u32(v as u32) as u64) << 32 | reverse_bits_u32((v >> 32) as u32) as u64
+}
+
+/// Reverse the lowest `n` bits of `value`, e.g. to turn a DEFLATE Huffman code of length `n`
+/// around. Bits above `n` are cleared in the result.
+///
+/// # Examples
+///
+/// ```rust
+/// use fiddling::reverse_bits_n;
+///
+/// assert_eq!(reverse_bits_n(0b1, 1), 0b1);
+/// assert_eq!(reverse_bits_n(0b10, 1), 0b0);
+/// assert_eq!(reverse_bits_n(0b111_10110, 5), 0b01101);
+/// assert_eq!(reverse_bits_n(0b1111_1100_0000_0001, 12), 0b1000_0000_0011);
+/// assert_eq!(reverse_bits_n(0b1011, 0), 0);
+/// assert_eq!(reverse_bits_n(1, 64), 1 << 63);
+/// ```
+pub fn reverse_bits_n(value: u64, n: u8) -> u64 {
+    assert!(n <= 64);
+    if n == 0 {
+        0
+    } else {
+        reverse_bits_u64(value) >> (64 - n)
+    }
+}
+
 static FIRST_N_BITS: [u8; 9] = [
     0b00000000, 0b00000001, 0b00000011, 0b00000111, 0b00001111, 0b00011111, 0b00111111, 0b01111111,
     0b11111111,
@@ -56,7 +108,7 @@ pub fn last_n_bits(byte: u8, n: u64) -> u8 {
     }
 }
 
-#[derive(PartialEq, Debug)]
+#[derive(Clone, Copy, PartialEq, Debug)]
 pub enum BitOrder {
     MsbFirst,
     LsbFirst,
@@ -104,8 +156,37 @@ pub enum BitOrder {
 
 /// ```
 pub fn n_bits_by_index(bytes: &[u8], n_bits: u8, bit_idx: usize, bit_order: BitOrder) -> u64 {
+    try_n_bits_by_index(bytes, n_bits, bit_idx, bit_order).expect("bit range out of bounds")
+}
+
+/// Like `n_bits_by_index`, but returns `None` instead of panicking if the requested bits don't
+/// fit in `bytes`.
+///
+/// # Examples
+///
+/// ```rust
+/// use fiddling::BitOrder::*;
+/// use fiddling::try_n_bits_by_index;
+///
+/// let bytes = [0b01010101, 0b00110011];
+/// assert_eq!(try_n_bits_by_index(&bytes, 8, 6, LsbFirst), Some(0b11001101));
+/// assert_eq!(try_n_bits_by_index(&bytes, 10, 6, LsbFirst), Some(0b00_11001101));
+/// assert_eq!(try_n_bits_by_index(&bytes, 11, 6, LsbFirst), None);
+/// assert_eq!(try_n_bits_by_index(&bytes, 0, 16, MsbFirst), Some(0));
+/// assert_eq!(try_n_bits_by_index(&bytes, 1, 16, MsbFirst), None);
+/// assert_eq!(try_n_bits_by_index(&bytes, 1, usize::MAX, MsbFirst), None);
+/// ```
+pub fn try_n_bits_by_index(
+    bytes: &[u8],
+    n_bits: u8,
+    bit_idx: usize,
+    bit_order: BitOrder,
+) -> Option<u64> {
     use BitOrder::*;
     assert!(n_bits <= 64);
+    if bit_idx.checked_add(n_bits as usize)? > bytes.len() * 8 {
+        return None;
+    }
 
     let original_byte_idx = bit_idx / 8;
     let mut byte_idx = original_byte_idx;
@@ -140,7 +221,7 @@ pub fn n_bits_by_index(bytes: &[u8], n_bits: u8, bit_idx: usize, bit_order: BitO
         n -= n_bits_read;
         byte_idx = byte_idx + 1;
     }
-    read_bits
+    Some(read_bits)
 }
 
 /// # Examples
@@ -200,27 +281,57 @@ pub fn n_bits_by_index(bytes: &[u8], n_bits: u8, bit_idx: usize, bit_order: BitO
 /// assert_eq!(f.read_bits(3, MsbFirst).unwrap(), 0b100);
 /// assert_eq!(f.read_bits(3, MsbFirst).unwrap(), 0b110);
 /// ```
-pub struct BitStream<R> {
+///
+/// The internal buffer is `BUF` bytes, which allows peeking and reading `(BUF - 1) * 8` bits at
+/// a time, up to 64 bits. Use `with_buffer_size` for a wider window than the default 32 bits.
+pub struct BitStream<R, const BUF: usize = 5> {
     inner: R,
-    buf: [u8; 5], // 64 bits (ought to be enough for everybody) + one extra byte
+    buf: [u8; BUF], // The bits that can be read at a time + one extra byte
     read_bit_pos: usize,
     load_byte_pos: usize,
+    buf_start_byte: u64, // Position of `buf[0]` in the `inner` reader
 }
 /// A reader for reading a byte stream on a bit basis,
 impl<R: Read> BitStream<R> {
     pub fn new(inner: R) -> BitStream<R> {
+        BitStream::with_buffer_size(inner)
+    }
+}
+
+impl<R: Read, const BUF: usize> BitStream<R, BUF> {
+    /// Create a `BitStream` with a `BUF` byte buffer.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use fiddling::BitStream;
+    /// use fiddling::BitOrder::*;
+    ///
+    /// let bytes: [u8; 8] = [0x01, 0x23, 0x45, 0x67, 0x89, 0xab, 0xcd, 0xef];
+    /// let mut f = BitStream::<_, 9>::with_buffer_size(&bytes[..]);
+    /// assert_eq!(f.max_peek_bits(), 64);
+    /// assert_eq!(f.read_bits(64, LsbFirst).unwrap(), 0xefcd_ab89_6745_2301);
+    /// ```
+    pub fn with_buffer_size(inner: R) -> BitStream<R, BUF> {
+        assert!(BUF >= 2, "BitStream needs a buffer of at least two bytes");
         BitStream {
             inner,
-            buf: [0; 5],
+            buf: [0; BUF],
             read_bit_pos: 0,
             load_byte_pos: 0,
+            buf_start_byte: 0,
         }
     }
 
+    /// The largest number of bits that can be peeked or read at a time
+    pub fn max_peek_bits(&self) -> usize {
+        cmp::min((BUF - 1) * 8, 64)
+    }
+
     /// Peek at the next `n` bits. Does not change the bit position of fiddler, but _can_ read more
     /// bytes from the `inner` reader.
     pub fn peek_bits(&mut self, n: usize, bo: BitOrder) -> io::Result<u64> {
-        assert!(n <= (self.buf.len() - 1) * 8);
+        assert!(n <= self.max_peek_bits());
         self.ensure_readable_bits(n)?;
         Ok(n_bits_by_index(&self.buf, n as u8, self.read_bit_pos, bo))
     }
@@ -228,14 +339,153 @@ impl<R: Read> BitStream<R> {
     /// Read (and consume) the next `n` bits from the `inner` reader.
     pub fn read_bits(&mut self, n: usize, bo: BitOrder) -> io::Result<u64> {
         let result = self.peek_bits(n, bo)?;
-        self.skip_bits(n);
+        self.skip_bits(n)?;
         Ok(result)
     }
 
-    pub fn skip_bits(&mut self, n: usize) {
-        // TODO Might as well be possible to skip more bytes
-        assert!(n <= (self.buf.len() - 1) * 8);
-        self.read_bit_pos += n;
+    /// Read (and consume) the next `n_bits` bits into `buf` in `MsbFirst` order, as if reading
+    /// `buf.len()` successive bytes with `read_bits(8, MsbFirst)`, but without the bit position
+    /// having to be at a byte boundary. If `n_bits` is not a multiple of 8, the remaining bits
+    /// are written to the most significant bits of the last byte and the rest of it is zeroed.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use fiddling::BitStream;
+    /// use fiddling::BitOrder::*;
+    ///
+    /// let bytes: [u8; 3] = [0b01010101, 0b00110011, 0b00001111];
+    /// let mut f = BitStream::new(&bytes[..]);
+    /// f.read_bits(4, MsbFirst).unwrap();
+    ///
+    /// let mut buf = [0u8; 2];
+    /// f.read_bits_into(&mut buf, 13).unwrap();
+    /// assert_eq!(buf, [0b10101100, 0b11001000]);
+    /// assert_eq!(f.read_bits(7, MsbFirst).unwrap(), 0b1110000);
+    /// ```
+    pub fn read_bits_into(&mut self, buf: &mut [u8], n_bits: usize) -> io::Result<()> {
+        assert!(n_bits <= buf.len() * 8);
+        let n_bytes = n_bits.div_ceil(8);
+        let mut bits_left = n_bits;
+        // Up to four bytes at a time, which always fits the internal buffer regardless of bit
+        // position
+        for chunk in buf[..n_bytes].chunks_mut(cmp::min(BUF - 1, 4)) {
+            let n = cmp::min(bits_left, chunk.len() * 8);
+            let bits = self.read_bits(n, BitOrder::MsbFirst)? << (32 - n);
+            chunk.copy_from_slice(&(bits as u32).to_be_bytes()[..chunk.len()]);
+            bits_left -= n;
+        }
+        Ok(())
+    }
+
+    /// Skip (consume) the next `n` bits. Bits past the internal buffer are read and discarded
+    /// from the `inner` reader, so `n` can be arbitrarily large.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use fiddling::BitStream;
+    /// use fiddling::BitOrder::*;
+    ///
+    /// let bytes: Vec<u8> = (0..100).collect();
+    /// let mut f = BitStream::new(&bytes[..]);
+    /// f.skip_bits(3).unwrap();
+    /// f.skip_bits(8 * 98).unwrap();
+    /// assert_eq!(f.read_bits(8, LsbFirst).unwrap(), 98 >> 3 | (99 << 5) & 0xff);
+    /// assert!(f.skip_bits(6).is_err());
+    /// ```
+    pub fn skip_bits(&mut self, n: usize) -> io::Result<()> {
+        let readable_bits = self.readable_bits();
+        if n <= readable_bits {
+            self.read_bit_pos += n;
+            return Ok(());
+        }
+
+        // Discard the whole buffer and the whole bytes after it straight from the inner reader
+        self.read_bit_pos += readable_bits;
+        self.reset();
+        let bits_left = n - readable_bits;
+        let bytes_to_skip = (bits_left / 8) as u64;
+        let skipped = io::copy(&mut (&mut self.inner).take(bytes_to_skip), &mut io::sink())?;
+        self.buf_start_byte += skipped;
+        if skipped < bytes_to_skip {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+
+        let within_byte_bits = bits_left % 8;
+        if within_byte_bits > 0 {
+            self.load_bytes(1)?;
+            self.read_bit_pos = within_byte_bits;
+        }
+        Ok(())
+    }
+
+    /// The number of bits that can be read without reading from the `inner` reader. This only
+    /// reflects the internal buffer, the `inner` reader may well have more bytes available.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use fiddling::BitStream;
+    /// use fiddling::BitOrder::*;
+    ///
+    /// let bytes: [u8; 2] = [0b01010101, 0b00110011];
+    /// let mut f = BitStream::new(&bytes[..]);
+    /// assert_eq!(f.buffered_bits(), 0);
+    ///
+    /// f.peek_bits(1, LsbFirst).unwrap();
+    /// assert_eq!(f.buffered_bits(), 8);
+    ///
+    /// f.read_bits(3, LsbFirst).unwrap();
+    /// assert_eq!(f.buffered_bits(), 5);
+    ///
+    /// f.read_bits(5, LsbFirst).unwrap();
+    /// assert_eq!(f.buffered_bits(), 0);
+    /// ```
+    pub fn buffered_bits(&self) -> usize {
+        self.readable_bits()
+    }
+
+    /// Whether all bits of the `inner` reader have been consumed. Tries to load one more byte if
+    /// none are buffered, so unlike a failing read this tells a clean end of stream apart from
+    /// other errors.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use fiddling::BitStream;
+    /// use fiddling::BitOrder::*;
+    ///
+    /// let bytes: [u8; 2] = [0b01010101, 0b00110011];
+    /// let mut f = BitStream::new(&bytes[..]);
+    /// assert!(!f.at_eof().unwrap());
+    /// assert_eq!(f.readable_bits(), 8);
+    ///
+    /// f.read_bits(15, LsbFirst).unwrap();
+    /// assert!(!f.at_eof().unwrap());
+    /// f.read_bits(1, LsbFirst).unwrap();
+    /// assert!(f.at_eof().unwrap());
+    /// assert_eq!(f.readable_bits(), 0);
+    /// ```
+    pub fn at_eof(&mut self) -> io::Result<bool> {
+        if self.readable_bits() > 0 {
+            return Ok(false);
+        }
+        if self.loadable_bits() < 8 {
+            self.rewind_buffer();
+        }
+        loop {
+            let pos = self.load_byte_pos;
+            match self.inner.read(&mut self.buf[pos..pos + 1]) {
+                Ok(0) => return Ok(true),
+                Ok(n) => {
+                    self.load_byte_pos += n;
+                    return Ok(false);
+                }
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e),
+            }
+        }
     }
 
     pub fn read_u16_le(&mut self) -> io::Result<u16> {
@@ -243,15 +493,86 @@ impl<R: Read> BitStream<R> {
         Ok(u16::from_le_bytes(buf))
     }
 
+    /// Iterate over the remaining bits one at a time, as 0 or 1, until the `inner` reader is
+    /// exhausted. The bits are consumed as they are yielded, so reads can be mixed with iteration.
+    /// Stops after yielding the first error.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use fiddling::BitStream;
+    /// use fiddling::BitOrder::*;
+    ///
+    /// let bytes: [u8; 2] = [0b01010101, 0b00110011];
+    /// let mut f = BitStream::new(&bytes[..]);
+    /// assert_eq!(f.read_bits(3, LsbFirst).unwrap(), 0b101);
+    /// let first_bits: Vec<u8> = f.bits().take(5).map(|b| b.unwrap()).collect();
+    /// assert_eq!(first_bits, vec![0, 1, 0, 1, 0]);
+    /// assert_eq!(f.read_bits(4, LsbFirst).unwrap(), 0b0011);
+    /// assert_eq!(f.bits().count(), 4);
+    /// ```
+    pub fn bits(&mut self) -> impl Iterator<Item = io::Result<u8>> + '_ {
+        let mut failed = false;
+        std::iter::from_fn(move || {
+            if failed {
+                return None;
+            }
+            let bit = match self.at_eof() {
+                Ok(true) => return None,
+                Ok(false) => self.read_bits(1, BitOrder::MsbFirst).map(|bit| bit as u8),
+                Err(e) => Err(e),
+            };
+            failed = bit.is_err();
+            Some(bit)
+        })
+    }
+
+    pub fn read_i16_le(&mut self) -> io::Result<i16> {
+        Ok(self.read_u16_le()? as i16)
+    }
+
+    pub fn read_i32_le(&mut self) -> io::Result<i32> {
+        let mut buf = [0; 4];
+        for byte in &mut buf {
+            *byte = self.read_next_byte()?;
+        }
+        Ok(i32::from_le_bytes(buf))
+    }
+
+    /// Read (and consume) the next `n` bits in `MsbFirst` order as a two's complement integer,
+    /// sign-extended to 64 bits.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use fiddling::BitStream;
+    ///
+    /// let bytes: [u8; 2] = [0b11111001, 0b00000000];
+    /// let mut f = BitStream::new(&bytes[..]);
+    /// assert_eq!(f.read_signed_bits(0).unwrap(), 0);
+    /// assert_eq!(f.read_signed_bits(2).unwrap(), -2);
+    /// assert_eq!(f.read_signed_bits(2).unwrap(), 1);
+    /// assert_eq!(f.read_signed_bits(4).unwrap(), -1);
+    /// ```
+    pub fn read_signed_bits(&mut self, n: usize) -> io::Result<i64> {
+        let bits = self.read_bits(n, BitOrder::MsbFirst)?;
+        if n == 0 {
+            return Ok(0);
+        }
+        let shift = 64 - n;
+        Ok(((bits << shift) as i64) >> shift)
+    }
+
     /// Skip to next byte boundary
-    pub fn skip_to_next_byte(&mut self) {
-        self.skip_bits((8 - (self.read_bit_pos % 8)) as usize);
+    pub fn skip_to_next_byte(&mut self) -> io::Result<()> {
+        self.skip_bits(8 - (self.read_bit_pos % 8))
     }
 
-    /// If not at start of byte, skip to start of next one
+    /// If not at start of byte, skip to start of next one. The rest of the current byte is
+    /// always buffered, so this never reads from the `inner` reader.
     pub fn skip_to_start_of_byte(&mut self) {
         if !self.is_at_byte_boundary() {
-            self.skip_to_next_byte();
+            self.read_bit_pos += 8 - (self.read_bit_pos % 8);
         }
     }
 
@@ -269,12 +590,19 @@ impl<R: Read> BitStream<R> {
         self.read_bit_pos / 8
     }
 
-    /// Load bytes from `inner` reader
+    /// Load bytes from `inner` reader. Bytes read before running out of input stay in the
+    /// buffer, so a failed peek doesn't lose them.
     fn load_bytes(&mut self, n_bytes: usize) -> io::Result<()> {
         assert!(self.load_byte_pos + n_bytes <= self.buf.len());
-        self.inner
-            .read_exact(&mut self.buf[self.load_byte_pos..self.load_byte_pos + n_bytes])?;
-        self.load_byte_pos += n_bytes;
+        let end = self.load_byte_pos + n_bytes;
+        while self.load_byte_pos < end {
+            match self.inner.read(&mut self.buf[self.load_byte_pos..end]) {
+                Ok(0) => return Err(io::ErrorKind::UnexpectedEof.into()),
+                Ok(n) => self.load_byte_pos += n,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(e),
+            }
+        }
         Ok(())
     }
 
@@ -293,13 +621,19 @@ impl<R: Read> BitStream<R> {
         }
         self.read_bit_pos %= 8;
         self.load_byte_pos -= read_byte_pos;
+        self.buf_start_byte += read_byte_pos as u64;
     }
 
+    /// Discard the buffer, so that reading continues from the current position of `inner`
     fn reset(&mut self) {
+        self.buf_start_byte += self.load_byte_pos as u64;
         self.load_byte_pos = 0;
         self.read_bit_pos = 0;
     }
-    fn readable_bits(&self) -> usize {
+
+    /// The number of bits that can be read without reading from the `inner` reader, same as
+    /// `buffered_bits`.
+    pub fn readable_bits(&self) -> usize {
         8 * self.load_byte_pos - self.read_bit_pos
     }
 
@@ -325,6 +659,12 @@ impl<R: Read> BitStream<R> {
         }
         Ok(())
     }
+    /// The number of bits consumed, counted from where the `inner` reader was positioned when
+    /// the `BitStream` was created.
+    pub fn bit_position(&self) -> u64 {
+        8 * self.buf_start_byte + self.read_bit_pos as u64
+    }
+
     pub fn get_ref(&self) -> &R {
         &self.inner
     }
@@ -339,6 +679,154 @@ impl<R: Read> BitStream<R> {
     }
 }
 
+impl<R: Read + Seek, const BUF: usize> BitStream<R, BUF> {
+    /// Seek to bit `bit_pos` from the start of the `inner` reader, discarding the buffer. Assumes
+    /// that `inner` was at its start when the `BitStream` was created, so that `bit_position`
+    /// and `bit_pos` agree.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use fiddling::BitStream;
+    /// use fiddling::BitOrder::*;
+    /// use std::io::Cursor;
+    ///
+    /// let bytes: [u8; 2] = [0b01010101, 0b00110011];
+    /// let mut f = BitStream::new(Cursor::new(&bytes[..]));
+    /// assert_eq!(f.read_bits(12, LsbFirst).unwrap(), 0b0011_01010101);
+    /// assert_eq!(f.bit_position(), 12);
+    ///
+    /// f.seek_to_bit(3).unwrap();
+    /// assert_eq!(f.bit_position(), 3);
+    /// assert_eq!(f.read_bits(6, LsbFirst).unwrap(), 0b1_01010);
+    /// assert_eq!(f.bit_position(), 9);
+    /// ```
+    pub fn seek_to_bit(&mut self, bit_pos: u64) -> io::Result<()> {
+        let byte_pos = bit_pos / 8;
+        self.inner.seek(SeekFrom::Start(byte_pos))?;
+        self.load_byte_pos = 0;
+        self.read_bit_pos = 0;
+        self.buf_start_byte = byte_pos;
+
+        let within_byte_pos = (bit_pos % 8) as usize;
+        if within_byte_pos > 0 {
+            self.load_bytes(1)?;
+            self.read_bit_pos = within_byte_pos;
+        }
+        Ok(())
+    }
+}
+
+/// A writer for writing a byte stream on a bit basis, the counterpart of `BitStream`. Bits are
+/// laid out so that `BitStream` reads them back with the same `BitOrder`.
+///
+/// Buffered bits are written to `inner` only when the buffer fills up or on `flush` and
+/// `into_inner`, which pad the last partial byte with zero bits.
+///
+/// # Examples
+///
+/// ```rust
+/// use fiddling::{BitStream, BitWriter};
+/// use fiddling::BitOrder::*;
+///
+/// let mut w = BitWriter::new(Vec::new());
+/// w.write_bits(0b101, 3, LsbFirst).unwrap();
+/// w.write_bits(0b010, 3, LsbFirst).unwrap();
+/// w.write_bits(0b101, 3, MsbFirst).unwrap();
+/// let bytes = w.into_inner().unwrap();
+/// assert_eq!(bytes, vec![0b01_010_101, 0b1]);
+///
+/// let mut f = BitStream::new(&bytes[..]);
+/// assert_eq!(f.read_bits(3, LsbFirst).unwrap(), 0b101);
+/// assert_eq!(f.read_bits(3, LsbFirst).unwrap(), 0b010);
+/// assert_eq!(f.read_bits(3, MsbFirst).unwrap(), 0b101);
+/// ```
+pub struct BitWriter<W: Write> {
+    inner: W,
+    buf: [u8; 5],
+    write_bit_pos: usize,
+}
+
+impl<W: Write> BitWriter<W> {
+    pub fn new(inner: W) -> BitWriter<W> {
+        BitWriter {
+            inner,
+            buf: [0; 5],
+            write_bit_pos: 0,
+        }
+    }
+
+    /// Write the lowest `n` bits of `value`. With `LsbFirst` the least significant bit goes
+    /// first into the stream, with `MsbFirst` the most significant one.
+    pub fn write_bits(&mut self, value: u64, n: usize, bo: BitOrder) -> io::Result<()> {
+        assert!(n <= 64);
+        for i in 0..n {
+            let bit_idx = match bo {
+                BitOrder::LsbFirst => i,
+                BitOrder::MsbFirst => n - 1 - i,
+            };
+            self.write_bit((value >> bit_idx) & 1 == 1)?;
+        }
+        Ok(())
+    }
+
+    /// Write `value` as two little endian bytes, starting from the next byte boundary
+    pub fn write_u16_le(&mut self, value: u16) -> io::Result<()> {
+        self.align_to_byte()?;
+        for &byte in &value.to_le_bytes() {
+            self.write_bits(byte as u64, 8, BitOrder::LsbFirst)?;
+        }
+        Ok(())
+    }
+
+    /// Pad with zero bits up to the next byte boundary
+    pub fn align_to_byte(&mut self) -> io::Result<()> {
+        let padding = (8 - self.write_bit_pos % 8) % 8;
+        self.write_bits(0, padding, BitOrder::LsbFirst)
+    }
+
+    /// Pad to a byte boundary and write all buffered bytes to the `inner` writer
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.align_to_byte()?;
+        self.write_buffer()?;
+        self.inner.flush()
+    }
+
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    /// Flush and return the `inner` writer
+    pub fn into_inner(mut self) -> io::Result<W> {
+        self.flush()?;
+        Ok(self.inner)
+    }
+
+    fn write_bit(&mut self, bit: bool) -> io::Result<()> {
+        if self.write_bit_pos == self.buf.len() * 8 {
+            self.write_buffer()?;
+        }
+        if bit {
+            self.buf[self.write_bit_pos / 8] |= 1 << (self.write_bit_pos % 8);
+        }
+        self.write_bit_pos += 1;
+        Ok(())
+    }
+
+    /// Write the whole bytes of `buf` to the `inner` writer, keeping a partial last byte
+    fn write_buffer(&mut self) -> io::Result<()> {
+        let whole_bytes = self.write_bit_pos / 8;
+        self.inner.write_all(&self.buf[..whole_bytes])?;
+        self.buf.copy_within(whole_bytes.., 0);
+        let len = self.buf.len();
+        for byte in &mut self.buf[len - whole_bytes..] {
+            *byte = 0;
+        }
+        self.write_bit_pos %= 8;
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::BitOrder::{LsbFirst, MsbFirst};
@@ -371,7 +859,7 @@ mod tests {
         f.peek_bits(9, MsbFirst).unwrap();
         assert!(!f.is_at_byte_boundary());
         f.peek_bits(9, MsbFirst).unwrap();
-        f.skip_to_next_byte();
+        f.skip_to_next_byte().unwrap();
         f.peek_bits(9, MsbFirst).unwrap();
         assert!(f.is_at_byte_boundary());
         f.peek_bits(9, MsbFirst).unwrap();
@@ -406,4 +894,206 @@ mod tests {
         // Skips to the start of next byte
         assert_eq!(f.read_u16_le().unwrap(), 0b1110_1111_1100_1101);
     }
+
+    #[test]
+    fn test_bit_writer_round_trip() {
+        // (value, n_bits, bit order) with every width from 1 to 17 bits and both orders
+        let values: Vec<(u64, usize, BitOrder)> = (1..=17)
+            .flat_map(|n| {
+                let value = 0x1_5a5a_u64.rotate_left(n as u32) & ((1 << n) - 1);
+                vec![(value, n, LsbFirst), (value ^ 1, n, MsbFirst)]
+            })
+            .collect();
+
+        let mut w = BitWriter::new(Vec::new());
+        for &(value, n, bo) in &values {
+            w.write_bits(value, n, bo).unwrap();
+        }
+        w.write_u16_le(0xbeef).unwrap();
+        w.write_bits(0b1, 1, LsbFirst).unwrap();
+        let bytes = w.into_inner().unwrap();
+
+        let mut f = BitStream::new(&bytes[..]);
+        for (value, n, bo) in values {
+            assert_eq!(f.read_bits(n, bo).unwrap(), value, "{} bits", n);
+        }
+        assert_eq!(f.read_u16_le().unwrap(), 0xbeef);
+        assert_eq!(f.read_bits(1, LsbFirst).unwrap(), 0b1);
+        assert_eq!(f.read_bits(7, LsbFirst).unwrap(), 0, "padded with zeros");
+        assert!(f.read_bits(1, LsbFirst).is_err());
+    }
+
+    #[test]
+    fn test_read_bits_into() {
+        let bytes: Vec<u8> = (0..24u8).map(|i| i.wrapping_mul(73) ^ 0x5a).collect();
+        for offset in 0..8 {
+            for &n_bits in &[0usize, 1, 7, 8, 9, 31, 32, 33, 64, 100, 150] {
+                // Assemble the expected bytes one bit at a time
+                let mut f = BitStream::new(&bytes[..]);
+                f.read_bits(offset, MsbFirst).unwrap();
+                let mut expected = vec![0u8; n_bits.div_ceil(8)];
+                for i in 0..n_bits {
+                    expected[i / 8] |= (f.read_bits(1, MsbFirst).unwrap() as u8) << (7 - i % 8);
+                }
+                let expected_next = f.read_bits(3, MsbFirst).unwrap();
+
+                let mut f = BitStream::new(&bytes[..]);
+                f.read_bits(offset, MsbFirst).unwrap();
+                let mut buf = vec![0xffu8; expected.len()];
+                f.read_bits_into(&mut buf, n_bits).unwrap();
+                assert_eq!(buf, expected, "offset {}, {} bits", offset, n_bits);
+                assert_eq!(f.read_bits(3, MsbFirst).unwrap(), expected_next);
+            }
+        }
+    }
+
+    #[test]
+    fn test_wide_buffer() {
+        let bytes: Vec<u8> = (0..32u8).map(|i| i.wrapping_mul(73) ^ 0x5a).collect();
+        for &bo in &[LsbFirst, MsbFirst] {
+            let mut narrow = BitStream::new(&bytes[..]);
+            let mut wide = BitStream::<_, 9>::with_buffer_size(&bytes[..]);
+            assert_eq!(wide.max_peek_bits(), 64);
+
+            narrow.read_bits(3, bo).unwrap();
+            wide.read_bits(3, bo).unwrap();
+            for &n in &[64, 33, 57, 1] {
+                let expected = match bo {
+                    LsbFirst => {
+                        let low = narrow.read_bits(n / 2, bo).unwrap();
+                        low | narrow.read_bits(n - n / 2, bo).unwrap() << (n / 2)
+                    }
+                    MsbFirst => {
+                        let high = narrow.read_bits(n / 2, bo).unwrap();
+                        high << (n - n / 2) | narrow.read_bits(n - n / 2, bo).unwrap()
+                    }
+                };
+                assert_eq!(
+                    wide.read_bits(n, bo).unwrap(),
+                    expected,
+                    "{} bits {:?}",
+                    n,
+                    bo
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_at_eof() {
+        let mut f = BitStream::new(&[][..]);
+        assert!(f.at_eof().unwrap());
+
+        // Fill the whole buffer before hitting the end, so at_eof has to make room
+        let bytes = [0xffu8; 7];
+        let mut f = BitStream::new(&bytes[..]);
+        f.read_bits(32, LsbFirst).unwrap();
+        f.read_bits(8, LsbFirst).unwrap();
+        assert_eq!(f.readable_bits(), 0);
+        assert!(!f.at_eof().unwrap());
+        assert_eq!(f.read_bits(16, LsbFirst).unwrap(), 0xffff);
+        assert!(f.at_eof().unwrap());
+        assert!(f.read_bits(1, LsbFirst).is_err());
+    }
+
+    #[test]
+    fn test_signed_reads() {
+        let bytes = [0b0000_1111u8, 0b1000_0000];
+        let mut f = BitStream::new(&bytes[..]);
+        assert_eq!(f.read_signed_bits(4).unwrap(), -1);
+        assert_eq!(f.read_signed_bits(4).unwrap(), 0);
+        assert_eq!(f.read_signed_bits(8).unwrap(), 1);
+
+        let mut f = BitStream::new(&bytes[..]);
+        assert_eq!(
+            f.read_signed_bits(16).unwrap(),
+            0b1111_0000_0000_0001u16 as i16 as i64
+        );
+
+        let bytes = [0xffu8; 8];
+        let mut f = BitStream::<_, 9>::with_buffer_size(&bytes[..]);
+        assert_eq!(f.read_signed_bits(64).unwrap(), -1);
+
+        let bytes = [0b11u8, 0, 0, 0, 0, 0, 0, 0];
+        let mut f = BitStream::<_, 9>::with_buffer_size(&bytes[..]);
+        assert_eq!(f.read_signed_bits(1).unwrap(), -1);
+        assert_eq!(f.read_signed_bits(63).unwrap(), i64::MIN / 2);
+
+        let bytes = [0xfeu8, 0xff, 0x00, 0x80, 0xfe, 0xff, 0xff, 0x7f];
+        let mut f = BitStream::new(&bytes[..]);
+        assert_eq!(f.read_i16_le().unwrap(), -2);
+        assert_eq!(f.read_i16_le().unwrap(), i16::MIN);
+        assert_eq!(f.read_i32_le().unwrap(), 0x7fff_fffe);
+    }
+
+    #[test]
+    fn test_seek_to_bit() {
+        let bytes: Vec<u8> = (0..16u8).map(|i| i.wrapping_mul(73) ^ 0x5a).collect();
+        let mut f = BitStream::new(io::Cursor::new(&bytes[..]));
+
+        // Read far enough for the buffer to have been rewound a few times
+        for _ in 0..7 {
+            f.read_bits(13, MsbFirst).unwrap();
+        }
+        assert_eq!(f.bit_position(), 91);
+
+        for &bit_pos in &[0u64, 5, 8, 91, 64, 17] {
+            let mut expected = BitStream::new(&bytes[..]);
+            for _ in 0..bit_pos {
+                expected.read_bits(1, MsbFirst).unwrap();
+            }
+            f.seek_to_bit(bit_pos).unwrap();
+            assert_eq!(f.bit_position(), bit_pos);
+            assert_eq!(
+                f.read_bits(20, MsbFirst).unwrap(),
+                expected.read_bits(20, MsbFirst).unwrap(),
+                "seek to {}",
+                bit_pos
+            );
+            assert_eq!(f.bit_position(), bit_pos + 20);
+        }
+    }
+
+    #[test]
+    fn test_bits() {
+        let bytes = [0b0000_1111u8, 0b1010_0001];
+        let mut f = BitStream::new(&bytes[..]);
+        let bits: Vec<u8> = f.bits().map(|bit| bit.unwrap()).collect();
+        assert_eq!(bits, vec![1, 1, 1, 1, 0, 0, 0, 0, 1, 0, 0, 0, 0, 1, 0, 1]);
+        assert!(f.at_eof().unwrap());
+        assert_eq!(f.bits().count(), 0);
+    }
+
+    #[test]
+    fn test_skip_bits_past_buffer() {
+        let bytes: Vec<u8> = (0..200u8).map(|i| i.wrapping_mul(73) ^ 0x5a).collect();
+        let mut f = BitStream::new(&bytes[..]);
+        let mut expected = BitStream::new(&bytes[..]);
+        for &n in &[5usize, 0, 33, 8, 100, 1, 512, 7, 13] {
+            f.skip_bits(n).unwrap();
+            for _ in 0..n {
+                expected.read_bits(1, LsbFirst).unwrap();
+            }
+            assert_eq!(f.bit_position(), expected.bit_position());
+            assert_eq!(
+                f.read_bits(11, LsbFirst).unwrap(),
+                expected.read_bits(11, LsbFirst).unwrap(),
+                "after skipping {} bits",
+                n
+            );
+        }
+        let bits_left = 8 * bytes.len() - f.bit_position() as usize;
+        assert!(f.skip_bits(bits_left + 1).is_err());
+    }
+
+    #[test]
+    fn test_failed_peek_keeps_bits() {
+        let bytes = [0b1010_1011u8];
+        let mut f = BitStream::new(&bytes[..]);
+        assert!(f.peek_bits(9, LsbFirst).is_err());
+        assert_eq!(f.peek_bits(8, LsbFirst).unwrap(), 0b1010_1011);
+        f.read_bits(2, LsbFirst).unwrap();
+        assert!(f.peek_bits(7, LsbFirst).is_err());
+        assert_eq!(f.read_bits(6, LsbFirst).unwrap(), 0b10_1010);
+    }
 }
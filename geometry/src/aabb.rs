@@ -0,0 +1,177 @@
+use crate::point::Point;
+use crate::ray::Ray;
+use math::vector::VecElem;
+
+/// An axis aligned bounding box, containing the points between the `min` and `max` corners,
+/// boundary included.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Aabb<T: VecElem, const N: usize> {
+    pub min: Point<T, N>,
+    pub max: Point<T, N>,
+}
+
+pub type Aabb2<T> = Aabb<T, 2>;
+pub type Aabb2f = Aabb2<f32>;
+pub type Aabb2i = Aabb2<i32>;
+
+pub type Aabb3<T> = Aabb<T, 3>;
+pub type Aabb3f = Aabb3<f32>;
+pub type Aabb3i = Aabb3<i32>;
+
+impl<T: VecElem + PartialOrd, const N: usize> Aabb<T, N> {
+    pub fn new(min: Point<T, N>, max: Point<T, N>) -> Aabb<T, N> {
+        Aabb { min, max }
+    }
+
+    /// The smallest box containing all of `points`, or `None` if there are none.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use geometry::aabb::Aabb2i;
+    /// use geometry::Point2i;
+    ///
+    /// let points = [Point2i::new(1, 5), Point2i::new(-2, 0), Point2i::new(4, 2)];
+    /// let aabb = Aabb2i::from_points(&points).unwrap();
+    /// assert_eq!(aabb, Aabb2i::new(Point2i::new(-2, 0), Point2i::new(4, 5)));
+    ///
+    /// assert_eq!(Aabb2i::from_points(&[]), None);
+    /// ```
+    pub fn from_points(points: &[Point<T, N>]) -> Option<Aabb<T, N>> {
+        let (first, rest) = points.split_first()?;
+        let (min, max) = rest
+            .iter()
+            .fold((*first.as_vector(), *first.as_vector()), |(min, max), p| {
+                (min.min(*p.as_vector()), max.max(*p.as_vector()))
+            });
+        Some(Aabb::new(min.into(), max.into()))
+    }
+
+    /// # Examples
+    ///
+    /// ```rust
+    /// use geometry::aabb::{Aabb2i, Aabb3f};
+    /// use geometry::{Point2i, Point3f};
+    ///
+    /// let square = Aabb2i::new(Point2i::new(0, 0), Point2i::new(2, 2));
+    /// assert!(square.contains(&Point2i::new(1, 1)));
+    /// assert!(square.contains(&Point2i::new(2, 0)));
+    /// assert!(!square.contains(&Point2i::new(3, 1)));
+    ///
+    /// let cube = Aabb3f::new(Point3f::new(-1.0, -1.0, -1.0), Point3f::new(1.0, 1.0, 1.0));
+    /// assert!(cube.contains(&Point3f::new(0.5, -0.5, 1.0)));
+    /// assert!(!cube.contains(&Point3f::new(0.5, -0.5, 1.5)));
+    /// ```
+    pub fn contains(&self, p: &Point<T, N>) -> bool {
+        (0..N).all(|i| self.min[i] <= p[i] && p[i] <= self.max[i])
+    }
+
+    /// Do the boxes overlap, touching included.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use geometry::aabb::{Aabb2i, Aabb3f};
+    /// use geometry::{Point2i, Point3f};
+    ///
+    /// let a = Aabb2i::new(Point2i::new(0, 0), Point2i::new(2, 2));
+    /// let b = Aabb2i::new(Point2i::new(1, 1), Point2i::new(3, 3));
+    /// let c = Aabb2i::new(Point2i::new(2, -1), Point2i::new(4, 0));
+    /// let d = Aabb2i::new(Point2i::new(3, 0), Point2i::new(4, 2));
+    /// assert!(a.intersects(&b));
+    /// assert!(a.intersects(&c));
+    /// assert!(!a.intersects(&d));
+    ///
+    /// let cube = Aabb3f::new(Point3f::new(0.0, 0.0, 0.0), Point3f::new(1.0, 1.0, 1.0));
+    /// let above = Aabb3f::new(Point3f::new(0.5, 0.5, 1.5), Point3f::new(2.0, 2.0, 2.0));
+    /// let across = Aabb3f::new(Point3f::new(0.5, -1.0, 0.5), Point3f::new(0.6, 2.0, 0.6));
+    /// assert!(!cube.intersects(&above));
+    /// assert!(cube.intersects(&across));
+    /// ```
+    pub fn intersects(&self, other: &Aabb<T, N>) -> bool {
+        (0..N).all(|i| self.min[i] <= other.max[i] && other.min[i] <= self.max[i])
+    }
+
+    /// The smallest box containing both `self` and `other`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use geometry::aabb::Aabb2i;
+    /// use geometry::Point2i;
+    ///
+    /// let a = Aabb2i::new(Point2i::new(0, 0), Point2i::new(2, 2));
+    /// let b = Aabb2i::new(Point2i::new(3, -1), Point2i::new(4, 1));
+    /// assert_eq!(a.union(&b), Aabb2i::new(Point2i::new(0, -1), Point2i::new(4, 2)));
+    /// ```
+    pub fn union(&self, other: &Aabb<T, N>) -> Aabb<T, N> {
+        Aabb::new(
+            self.min.as_vector().min(*other.min.as_vector()).into(),
+            self.max.as_vector().max(*other.max.as_vector()).into(),
+        )
+    }
+
+    /// # Examples
+    ///
+    /// ```rust
+    /// use geometry::aabb::Aabb3i;
+    /// use geometry::{Point3f, Point3i};
+    ///
+    /// let aabb = Aabb3i::new(Point3i::new(0, 0, 0), Point3i::new(1, 2, 3));
+    /// assert_eq!(aabb.center(), Point3f::new(0.5, 1.0, 1.5));
+    /// ```
+    pub fn center(&self) -> Point<f32, N> {
+        self.min.lerp(&self.max, 0.5)
+    }
+}
+
+impl<const N: usize> Aabb<f32, N> {
+    /// The ray parameters at which `ray` enters and exits the box, found with the slab method.
+    /// The entry is negative if the ray starts inside the box. Returns `None` if the ray misses
+    /// the box or the box is entirely behind the ray's origin.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use geometry::aabb::Aabb3f;
+    /// use geometry::{Point3f, Ray3f};
+    /// use math::Vec3f;
+    ///
+    /// let cube = Aabb3f::new(Point3f::new(0.0, 0.0, 0.0), Point3f::new(2.0, 2.0, 2.0));
+    ///
+    /// let through = Ray3f::new(Point3f::new(-1.0, 0.0, 1.0), Vec3f::new(1.0, 1.0, 0.0));
+    /// assert_eq!(cube.intersect_ray(&through), Some((1.0, 2.0)));
+    ///
+    /// // Along the axes
+    /// let along_x = Ray3f::new(Point3f::new(-2.0, 1.0, 1.0), Vec3f::new(2.0, 0.0, 0.0));
+    /// assert_eq!(cube.intersect_ray(&along_x), Some((1.0, 2.0)));
+    /// let inside = Ray3f::new(Point3f::new(1.0, 1.0, 1.0), Vec3f::new(0.0, 0.0, -1.0));
+    /// assert_eq!(cube.intersect_ray(&inside), Some((-1.0, 1.0)));
+    ///
+    /// let miss = Ray3f::new(Point3f::new(-1.0, 0.0, 1.0), Vec3f::new(1.0, 3.0, 0.0));
+    /// assert_eq!(cube.intersect_ray(&miss), None);
+    /// let beside = Ray3f::new(Point3f::new(-1.0, 3.0, 1.0), Vec3f::new(1.0, 0.0, 0.0));
+    /// assert_eq!(cube.intersect_ray(&beside), None);
+    /// let behind = Ray3f::new(Point3f::new(3.0, 1.0, 1.0), Vec3f::new(1.0, 0.0, 0.0));
+    /// assert_eq!(cube.intersect_ray(&behind), None);
+    /// ```
+    pub fn intersect_ray(&self, ray: &Ray<f32, N>) -> Option<(f32, f32)> {
+        let mut entry = f32::NEG_INFINITY;
+        let mut exit = f32::INFINITY;
+        for i in 0..N {
+            // Infinite for rays parallel to the slab, leaving the range unbounded if the origin
+            // is between the planes and empty otherwise
+            let dir_inv = 1.0 / ray.dir[i];
+            let t0 = (self.min[i] - ray.origin[i]) * dir_inv;
+            let t1 = (self.max[i] - ray.origin[i]) * dir_inv;
+            // f32::min and f32::max ignore the NaN of an origin exactly on a parallel slab
+            entry = entry.max(t0.min(t1));
+            exit = exit.min(t0.max(t1));
+        }
+        if entry <= exit && exit >= 0.0 {
+            Some((entry, exit))
+        } else {
+            None
+        }
+    }
+}
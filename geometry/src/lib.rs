@@ -1,8 +1,16 @@
+pub mod aabb;
 pub mod line_segment;
+pub mod plane;
 pub mod point;
+pub mod polygon;
+pub mod ray;
 pub mod transform;
 pub mod triangle;
 
+pub use aabb::Aabb;
+
+pub use plane::Plane3f;
+
 pub use point::Point2;
 pub use point::Point2f;
 pub use point::Point2i;
@@ -20,6 +28,11 @@ pub use line_segment::LineSegment3;
 pub use line_segment::LineSegment3f;
 pub use line_segment::LineSegment3i;
 
+pub use polygon::Polygon2f;
+
+pub use ray::Ray2f;
+pub use ray::Ray3f;
+
 pub use triangle::Triangle2;
 pub use triangle::Triangle2f;
 pub use triangle::Triangle2i;
@@ -1,5 +1,6 @@
 use crate::point::Point;
-use math::vector::VecElem;
+use crate::Point2f;
+use math::vector::{VecElem, Zero};
 
 pub struct LineSegment<'a, T: VecElem, const N: usize> {
     pub start: &'a Point<T, N>,
@@ -10,6 +11,154 @@ impl<'a, T: VecElem, const N: usize> LineSegment<'a, T, N> {
     pub fn new(start: &'a Point<T, N>, end: &'a Point<T, N>) -> LineSegment<'a, T, N> {
         LineSegment { start, end }
     }
+
+    /// # Examples
+    ///
+    /// ```rust
+    /// use geometry::{LineSegment2i, Point2i};
+    ///
+    /// let start = Point2i::new(1, 1);
+    /// let end = Point2i::new(4, 5);
+    /// assert_eq!(LineSegment2i::new(&start, &end).length(), 5.0);
+    /// ```
+    pub fn length(&self) -> f32 {
+        self.start.distance(self.end)
+    }
+
+    /// The point at `t` along the segment, `start` at `t = 0` and `end` at `t = 1`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use geometry::{LineSegment2i, Point2f, Point2i};
+    ///
+    /// let start = Point2i::new(1, 1);
+    /// let end = Point2i::new(4, 5);
+    /// let segment = LineSegment2i::new(&start, &end);
+    /// assert_eq!(segment.point_at(0.0), Point2f::new(1.0, 1.0));
+    /// assert_eq!(segment.point_at(0.5), Point2f::new(2.5, 3.0));
+    /// assert_eq!(segment.point_at(1.0), Point2f::new(4.0, 5.0));
+    /// ```
+    pub fn point_at(&self, t: f32) -> Point<f32, N> {
+        self.start.lerp(self.end, t)
+    }
+}
+
+impl<'a, const N: usize> LineSegment<'a, f32, N> {
+    /// The point on the segment closest to `p`. For a segment of zero length, that's `start`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use geometry::{LineSegment2f, Point2f};
+    ///
+    /// let (start, end) = (Point2f::new(0.0, 0.0), Point2f::new(4.0, 0.0));
+    /// let segment = LineSegment2f::new(&start, &end);
+    /// assert_eq!(segment.closest_point(&Point2f::new(1.0, 3.0)), Point2f::new(1.0, 0.0));
+    /// assert_eq!(segment.closest_point(&Point2f::new(2.5, -1.0)), Point2f::new(2.5, 0.0));
+    /// // Past the ends
+    /// assert_eq!(segment.closest_point(&Point2f::new(-2.0, 1.0)), start);
+    /// assert_eq!(segment.closest_point(&Point2f::new(7.0, 7.0)), end);
+    /// ```
+    pub fn closest_point(&self, p: &Point<f32, N>) -> Point<f32, N> {
+        let d = *self.end - *self.start;
+        let length_squared = d.length_squared();
+        if length_squared == 0.0 {
+            return *self.start;
+        }
+        let t = ((*p - *self.start).dot(d) / length_squared).clamp(0.0, 1.0);
+        self.point_at(t)
+    }
+
+    /// The distance from `p` to the closest point on the segment.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use geometry::{LineSegment2f, Point2f};
+    ///
+    /// let (start, end) = (Point2f::new(0.0, 0.0), Point2f::new(4.0, 0.0));
+    /// let segment = LineSegment2f::new(&start, &end);
+    /// assert_eq!(segment.distance_to(&Point2f::new(1.0, 3.0)), 3.0);
+    /// assert_eq!(segment.distance_to(&Point2f::new(7.0, 4.0)), 5.0);
+    /// assert_eq!(segment.distance_to(&Point2f::new(2.0, 0.0)), 0.0);
+    /// ```
+    pub fn distance_to(&self, p: &Point<f32, N>) -> f32 {
+        self.closest_point(p).distance(p)
+    }
+}
+
+impl<'a, T: VecElem> LineSegment2<'a, T> {
+    /// The intersection point of the segments, endpoints included, or `None` if they don't
+    /// meet. For collinear segments that overlap, returns the point of the overlap closest to
+    /// `self.start`. Segments of zero length never intersect.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use geometry::{LineSegment2i, Point2f, Point2i};
+    ///
+    /// let (p0, p1) = (Point2i::new(0, 0), Point2i::new(4, 4));
+    /// let (p2, p3) = (Point2i::new(0, 4), Point2i::new(4, 0));
+    /// let diagonal = LineSegment2i::new(&p0, &p1);
+    ///
+    /// // X crossing
+    /// let other_diagonal = LineSegment2i::new(&p2, &p3);
+    /// assert_eq!(diagonal.intersect(&other_diagonal), Some(Point2f::new(2.0, 2.0)));
+    ///
+    /// // Parallel
+    /// let (p4, p5) = (Point2i::new(1, 0), Point2i::new(5, 4));
+    /// assert_eq!(diagonal.intersect(&LineSegment2i::new(&p4, &p5)), None);
+    ///
+    /// // T junction, touching at an endpoint, and one stopping just short
+    /// let (p6, p7) = (Point2i::new(2, 2), Point2i::new(6, -2));
+    /// let t_junction = LineSegment2i::new(&p6, &p7);
+    /// assert_eq!(diagonal.intersect(&t_junction), Some(Point2f::new(2.0, 2.0)));
+    /// assert_eq!(t_junction.intersect(&diagonal), Some(Point2f::new(2.0, 2.0)));
+    /// let p8 = Point2i::new(1, 3);
+    /// assert_eq!(diagonal.intersect(&LineSegment2i::new(&p2, &p8)), None);
+    ///
+    /// // Collinear, overlapping and not
+    /// let (p9, p10) = (Point2i::new(6, 6), Point2i::new(3, 3));
+    /// assert_eq!(diagonal.intersect(&LineSegment2i::new(&p9, &p10)), Some(Point2f::new(3.0, 3.0)));
+    /// let (p11, p12) = (Point2i::new(5, 5), Point2i::new(6, 6));
+    /// assert_eq!(diagonal.intersect(&LineSegment2i::new(&p11, &p12)), None);
+    /// ```
+    pub fn intersect(&self, other: &LineSegment2<T>) -> Option<Point2f> {
+        let r = *self.end - *self.start;
+        let s = *other.end - *other.start;
+        let pq = *other.start - *self.start;
+        if r.is_zero() || s.is_zero() {
+            return None;
+        }
+
+        let denom = r.perp_dot(s).as_f32();
+        if denom == 0.0 {
+            if !pq.perp_dot(r).is_zero() {
+                // Parallel, but on different lines
+                return None;
+            }
+            // Collinear, find the overlap of the segments as multiples of r
+            let r_length_squared = r.length_squared().as_f32();
+            let t0 = pq.dot(r).as_f32() / r_length_squared;
+            let t1 = t0 + s.dot(r).as_f32() / r_length_squared;
+            let t_min = t0.min(t1).max(0.0);
+            let t_max = t0.max(t1).min(1.0);
+            return if t_min <= t_max {
+                Some(self.point_at(t_min))
+            } else {
+                None
+            };
+        }
+
+        let t = pq.perp_dot(s).as_f32() / denom;
+        let u = pq.perp_dot(r).as_f32() / denom;
+        if (0.0..=1.0).contains(&t) && (0.0..=1.0).contains(&u) {
+            Some(self.point_at(t))
+        } else {
+            None
+        }
+    }
 }
 
 pub type LineSegment2<'a, T> = LineSegment<'a, T, 2>;
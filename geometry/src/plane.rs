@@ -0,0 +1,119 @@
+use crate::{Point3f, Triangle3f};
+use math::Vec3f;
+
+/// A plane of the points `p` for which `normal · p + d = 0`, with a unit length normal
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Plane3f {
+    normal: Vec3f,
+    d: f32,
+}
+
+impl Plane3f {
+    /// The plane through `point` perpendicular to `normal`, which must not be of zero length.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use geometry::plane::Plane3f;
+    /// use geometry::Point3f;
+    /// use math::Vec3f;
+    ///
+    /// let plane = Plane3f::from_point_normal(Point3f::new(1.0, 2.0, 3.0), Vec3f::new(0.0, 0.0, 2.0));
+    /// assert_eq!(plane.normal(), Vec3f::new(0.0, 0.0, 1.0));
+    /// assert_eq!(plane.d(), -3.0);
+    /// ```
+    pub fn from_point_normal(point: Point3f, normal: Vec3f) -> Plane3f {
+        let normal = normal.unit();
+        Plane3f {
+            normal,
+            d: -normal.dot(*point.as_vector()),
+        }
+    }
+
+    /// The plane through the three points, with the normal pointing toward the side from which
+    /// they go around counterclockwise. Returns `None` for collinear points.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use geometry::plane::Plane3f;
+    /// use geometry::Point3f;
+    /// use math::Vec3f;
+    ///
+    /// let p0 = Point3f::new(0.0, 0.0, 1.0);
+    /// let p1 = Point3f::new(1.0, 0.0, 1.0);
+    /// let p2 = Point3f::new(0.0, 1.0, 1.0);
+    /// let plane = Plane3f::from_points(&p0, &p1, &p2).unwrap();
+    /// assert_eq!(plane.normal(), Vec3f::new(0.0, 0.0, 1.0));
+    /// assert_eq!(plane.d(), -1.0);
+    ///
+    /// let p3 = Point3f::new(2.0, 0.0, 1.0);
+    /// assert_eq!(Plane3f::from_points(&p0, &p1, &p3), None);
+    /// ```
+    pub fn from_points(p0: &Point3f, p1: &Point3f, p2: &Point3f) -> Option<Plane3f> {
+        Plane3f::from_triangle(&Triangle3f::new(p0, p1, p2))
+    }
+
+    /// The plane of the triangle, with the normal of `Triangle3f::normal`. Returns `None` for
+    /// degenerate triangles.
+    pub fn from_triangle(triangle: &Triangle3f) -> Option<Plane3f> {
+        let normal = triangle.normal().try_unit()?;
+        Some(Plane3f {
+            normal,
+            d: -normal.dot(*triangle.points[0].as_vector()),
+        })
+    }
+
+    pub fn normal(&self) -> Vec3f {
+        self.normal
+    }
+
+    pub fn d(&self) -> f32 {
+        self.d
+    }
+
+    /// The distance of `p` from the plane, positive on the side the normal points to and
+    /// negative on the other.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use geometry::plane::Plane3f;
+    /// use geometry::Point3f;
+    /// use math::Vec3f;
+    ///
+    /// let plane = Plane3f::from_point_normal(Point3f::new(0.0, 0.0, 1.0), Vec3f::new(0.0, 0.0, 1.0));
+    /// assert_eq!(plane.signed_distance(&Point3f::new(2.0, -1.0, 3.0)), 2.0);
+    /// assert_eq!(plane.signed_distance(&Point3f::new(2.0, -1.0, -0.5)), -1.5);
+    /// assert_eq!(plane.signed_distance(&Point3f::new(2.0, -1.0, 1.0)), 0.0);
+    /// ```
+    pub fn signed_distance(&self, p: &Point3f) -> f32 {
+        self.normal.dot(*p.as_vector()) + self.d
+    }
+
+    /// The point on the plane closest to `p`
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use geometry::plane::Plane3f;
+    /// use geometry::Point3f;
+    /// use math::{assert_eq_eps, Vec3f};
+    ///
+    /// let p0 = Point3f::new(1.0, 0.0, 0.0);
+    /// let p1 = Point3f::new(0.0, 1.0, 0.0);
+    /// let p2 = Point3f::new(0.0, 0.0, 1.0);
+    /// let plane = Plane3f::from_points(&p0, &p1, &p2).unwrap();
+    ///
+    /// let above = Point3f::new(1.0, 1.0, 1.0);
+    /// let below = Point3f::new(0.0, 0.0, 0.0);
+    /// let on = Point3f::new(0.5, 0.5, 0.0);
+    /// let expected = Vec3f::new(1.0, 1.0, 1.0) / 3.0;
+    /// assert_eq_eps!(*plane.project(&above).as_vector(), expected, 1e-6);
+    /// assert_eq_eps!(*plane.project(&below).as_vector(), expected, 1e-6);
+    /// assert_eq_eps!(*plane.project(&on).as_vector(), *on.as_vector(), 1e-6);
+    /// ```
+    pub fn project(&self, p: &Point3f) -> Point3f {
+        *p + self.normal * -self.signed_distance(p)
+    }
+}
@@ -21,6 +21,36 @@ impl<T: VecElem, const N: usize> Point<T, N> {
     pub fn as_vector(&self) -> &Vector<T, N> {
         &self.0
     }
+
+    /// # Examples
+    ///
+    /// ```rust
+    /// use geometry::Point3f;
+    ///
+    /// let a = Point3f::new(1.0, 2.0, 2.0);
+    /// assert_eq!(a.distance(&Point3f::new(0.0, 0.0, 0.0)), 3.0);
+    /// assert_eq!(a.distance(&a), 0.0);
+    /// ```
+    pub fn distance(&self, other: &Self) -> f32 {
+        self.0.distance(other.0)
+    }
+
+    /// Linearly interpolate from `self` at `t = 0` to `other` at `t = 1`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use geometry::Point3f;
+    ///
+    /// let a = Point3f::new(1.0, 2.0, 3.0);
+    /// let b = Point3f::new(3.0, 6.0, -1.0);
+    /// assert_eq!(a.lerp(&b, 0.0), a);
+    /// assert_eq!(a.lerp(&b, 1.0), b);
+    /// assert_eq!(a.lerp(&b, 0.5), Point3f::new(2.0, 4.0, 1.0));
+    /// ```
+    pub fn lerp(&self, other: &Self, t: f32) -> Point<f32, N> {
+        Point(self.0.lerp(other.0, t))
+    }
 }
 
 impl<T: VecElem> Point2<T> {
@@ -0,0 +1,89 @@
+use crate::Point2f;
+
+/// A simple polygon, i.e. one whose edges don't intersect, given by its vertices in order. The
+/// last vertex connects back to the first one.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Polygon2f {
+    pub points: Vec<Point2f>,
+}
+
+impl Polygon2f {
+    pub fn new(points: Vec<Point2f>) -> Self {
+        Polygon2f { points }
+    }
+
+    /// The edges of the polygon as (start, end) pairs, including the closing edge
+    pub fn edges(&self) -> impl Iterator<Item = (&Point2f, &Point2f)> {
+        self.points.iter().zip(self.points.iter().cycle().skip(1))
+    }
+
+    /// Area of the polygon by the shoelace formula, positive if the vertices go counterclockwise
+    /// (with y pointing up).
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use geometry::polygon::Polygon2f;
+    /// use geometry::Point2f;
+    ///
+    /// let mut square = Polygon2f::new(vec![
+    ///     Point2f::new(0.0, 0.0),
+    ///     Point2f::new(2.0, 0.0),
+    ///     Point2f::new(2.0, 2.0),
+    ///     Point2f::new(0.0, 2.0),
+    /// ]);
+    /// assert_eq!(square.signed_area(), 4.0);
+    ///
+    /// square.points.reverse();
+    /// assert_eq!(square.signed_area(), -4.0);
+    /// ```
+    pub fn signed_area(&self) -> f32 {
+        self.edges()
+            .map(|(p0, p1)| p0.x() * p1.y() - p1.x() * p0.y())
+            .sum::<f32>()
+            / 2.0
+    }
+
+    /// Is `p` inside the polygon, by casting a ray from `p` along positive x and counting the
+    /// edges it crosses.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use geometry::polygon::Polygon2f;
+    /// use geometry::Point2f;
+    ///
+    /// // A U shape, open at the top
+    /// let u = Polygon2f::new(vec![
+    ///     Point2f::new(0.0, 0.0),
+    ///     Point2f::new(3.0, 0.0),
+    ///     Point2f::new(3.0, 3.0),
+    ///     Point2f::new(2.0, 3.0),
+    ///     Point2f::new(2.0, 1.0),
+    ///     Point2f::new(1.0, 1.0),
+    ///     Point2f::new(1.0, 3.0),
+    ///     Point2f::new(0.0, 3.0),
+    /// ]);
+    ///
+    /// assert!(u.contains(&Point2f::new(0.5, 2.0)));
+    /// assert!(u.contains(&Point2f::new(1.5, 0.5)));
+    /// assert!(u.contains(&Point2f::new(2.5, 2.5)));
+    /// // In the concavity
+    /// assert!(!u.contains(&Point2f::new(1.5, 2.0)));
+    /// // Outside
+    /// assert!(!u.contains(&Point2f::new(4.0, 1.0)));
+    /// assert!(!u.contains(&Point2f::new(-1.0, 1.0)));
+    /// ```
+    pub fn contains(&self, p: &Point2f) -> bool {
+        let mut inside = false;
+        for (p0, p1) in self.edges() {
+            if (p0.y() > p.y()) != (p1.y() > p.y()) {
+                let x_crossing = p0.x() + (p.y() - p0.y()) / (p1.y() - p0.y()) * (p1.x() - p0.x());
+                if p.x() < x_crossing {
+                    inside = !inside;
+                }
+            }
+        }
+        inside
+    }
+}
@@ -0,0 +1,40 @@
+use crate::point::Point;
+use math::vector::{VecElem, Vector};
+
+/// A half-line starting from `origin` in the direction `dir`
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Ray<T: VecElem, const N: usize> {
+    pub origin: Point<T, N>,
+    pub dir: Vector<T, N>,
+}
+
+pub type Ray2<T> = Ray<T, 2>;
+pub type Ray2f = Ray2<f32>;
+
+pub type Ray3<T> = Ray<T, 3>;
+pub type Ray3f = Ray3<f32>;
+
+impl<T: VecElem, const N: usize> Ray<T, N> {
+    pub fn new(origin: Point<T, N>, dir: Vector<T, N>) -> Ray<T, N> {
+        Ray { origin, dir }
+    }
+}
+
+impl<const N: usize> Ray<f32, N> {
+    /// The point `origin + t * dir`
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use geometry::ray::Ray3f;
+    /// use geometry::Point3f;
+    /// use math::Vec3f;
+    ///
+    /// let ray = Ray3f::new(Point3f::new(1.0, 0.0, 0.0), Vec3f::new(0.0, 2.0, 0.0));
+    /// assert_eq!(ray.at(0.0), ray.origin);
+    /// assert_eq!(ray.at(1.5), Point3f::new(1.0, 3.0, 0.0));
+    /// ```
+    pub fn at(&self, t: f32) -> Point<f32, N> {
+        self.origin + self.dir * t
+    }
+}
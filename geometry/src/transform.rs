@@ -1,5 +1,6 @@
 use crate::{Point3f, Point4f};
-use math::{Matrix3f, Matrix4f, Vec3f, Vec4f};
+use math::vector::Zero;
+use math::{Matrix3f, Matrix4f, Quaternion, Vec3f, Vec4f};
 use std::ops::Mul;
 
 #[derive(Copy, Clone, Debug, PartialEq)]
@@ -14,10 +15,178 @@ impl Transform {
         &mut self.0
     }
 
+    /// # Examples
+    ///
+    /// ```rust
+    /// use geometry::transform::Transform;
+    /// use math::{assert_eq_eps, Matrix4f, Vec3f};
+    /// use math::matrix::{One, Zero};
+    ///
+    /// let t = Transform::translation(Vec3f::new(1.0, -2.0, 3.0))
+    ///     * Transform::rotation(0.7, Vec3f::new(1.0, 2.0, 3.0).unit())
+    ///     * Transform::frustum_projection(1.0, 1.5, 0.1, 100.0);
+    /// assert_eq_eps!(*(t * t.inverse().unwrap()).as_matrix(), Matrix4f::one(), 1e-5);
+    ///
+    /// assert_eq!(Transform::from(Matrix4f::diagonal(Vec3f::zero().xyz1())).inverse(), None);
+    /// ```
     pub fn inverse(&self) -> Option<Transform> {
         self.as_matrix().inverse().map(|m| m.into())
     }
 
+    /// Is the bottom row of the matrix `0 0 0 1`, i.e. does the transform consist of a linear
+    /// part and a translation only.
+    pub fn is_affine(&self) -> bool {
+        let m = self.as_matrix();
+        m.get(3, 0) == 0.0 && m.get(3, 1) == 0.0 && m.get(3, 2) == 0.0 && m.get(3, 3) == 1.0
+    }
+
+    /// Inverse of an affine transform, computed by inverting the 3x3 linear part and transforming
+    /// the translation with it. Much cheaper than the general `inverse`. Returns `None` if the
+    /// transform is not affine or not invertible.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use geometry::transform::Transform;
+    /// use math::{assert_eq_eps, Matrix3f, Vec3f};
+    ///
+    /// let trs = Transform::translation(Vec3f::new(1.0, -2.0, 3.0))
+    ///     * Transform::rotation(0.7, Vec3f::new(1.0, 2.0, 3.0).unit())
+    ///     * Transform::from(Matrix3f::new(2.0, 0.0, 0.0, 0.0, 3.0, 0.0, 0.0, 0.0, 0.5));
+    /// assert!(trs.is_affine());
+    /// assert_eq_eps!(
+    ///     *trs.inverse_affine().unwrap().as_matrix(),
+    ///     *trs.inverse().unwrap().as_matrix(),
+    ///     0.00001
+    /// );
+    ///
+    /// let projection = Transform::frustum_projection(1.0, 1.0, 0.1, 100.0);
+    /// assert!(!projection.is_affine());
+    /// assert_eq!(projection.inverse_affine(), None);
+    /// ```
+    pub fn inverse_affine(&self) -> Option<Transform> {
+        if !self.is_affine() {
+            return None;
+        }
+        let m = self.as_matrix();
+        let linear_inverse = self.linear().inverse()?;
+        let translation = -(linear_inverse * m.col(3).xyz());
+
+        let mut inverse = Matrix4f::from(linear_inverse);
+        inverse.set(0, 3, translation.x());
+        inverse.set(1, 3, translation.y());
+        inverse.set(2, 3, translation.z());
+        Some(inverse.into())
+    }
+
+    /// Transform the point `p` and divide by the resulting w. Multiplying with the transform
+    /// directly gives the homogeneous point instead.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use geometry::transform::Transform;
+    /// use geometry::Point3f;
+    /// use math::{Matrix4f, Vec3f, Vec4f};
+    ///
+    /// let translation = Transform::translation(Vec3f::new(1.0, -2.0, 3.0));
+    /// let p = Point3f::new(1.0, 1.0, 1.0);
+    /// assert_eq!(translation.transform_point(p), Point3f::new(2.0, -1.0, 4.0));
+    ///
+    /// let double_w = Transform::from(Matrix4f::diagonal(Vec4f::new(1.0, 1.0, 1.0, 2.0)));
+    /// assert_eq!(double_w.transform_point(p), Point3f::new(0.5, 0.5, 0.5));
+    /// ```
+    pub fn transform_point(&self, p: Point3f) -> Point3f {
+        (*self * p).perspective_divide().xyz()
+    }
+
+    /// The upper-left 3x3 part of the transform, i.e. the linear part without the translation
+    pub fn linear(&self) -> Matrix3f {
+        let m = self.as_matrix();
+        Matrix3f::from_columns(m.col(0).xyz(), m.col(1).xyz(), m.col(2).xyz())
+    }
+
+    /// Split an affine transform into its translation, rotation and scale, so that it equals
+    /// `translation(t) * Transform::from(r) * scale(s)` for the returned `(t, r, s)`. The
+    /// linear part is assumed to have no shear or reflection; scaling applied after a rotation is
+    /// only recovered exactly if it is uniform.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use geometry::transform::Transform;
+    /// use math::{assert_eq_eps, Matrix3f, Vec3f};
+    ///
+    /// let t = Vec3f::new(1.0, -2.0, 3.0);
+    /// let r = Matrix3f::rotation(0.7, Vec3f::new(1.0, 2.0, 3.0).unit());
+    /// let s = Vec3f::new(2.0, 3.0, 0.5);
+    ///
+    /// let trs = Transform::translation(t) * Transform::from(r) * Transform::scale(s);
+    /// let (t2, r2, s2) = trs.decompose();
+    /// assert_eq_eps!(t2, t, 1e-6);
+    /// assert_eq_eps!(r2, r, 1e-6);
+    /// assert_eq_eps!(s2, s, 1e-6);
+    ///
+    /// let tsr = Transform::translation(t) * Transform::uniform_scale(2.0) * Transform::from(r);
+    /// let (t2, r2, s2) = tsr.decompose();
+    /// assert_eq_eps!(t2, t, 1e-6);
+    /// assert_eq_eps!(r2, r, 1e-6);
+    /// assert_eq_eps!(s2, Vec3f::new(2.0, 2.0, 2.0), 1e-6);
+    /// ```
+    pub fn decompose(&self) -> (Vec3f, Matrix3f, Vec3f) {
+        let translation = self.as_matrix().col(3).xyz();
+        let linear = self.linear();
+        let scale = Vec3f::new(
+            linear.col(0).length(),
+            linear.col(1).length(),
+            linear.col(2).length(),
+        );
+        let rotation = Matrix3f::from_columns(
+            linear.col(0) / scale.x(),
+            linear.col(1) / scale.y(),
+            linear.col(2) / scale.z(),
+        );
+        (translation, rotation, scale)
+    }
+
+    /// The matrix transforming normals, i.e. the inverse-transpose of the linear part of the
+    /// transform. Returns `None` if the linear part is not invertible.
+    pub fn normal_matrix(&self) -> Option<Matrix3f> {
+        self.linear().inverse().map(|m| m.transpose())
+    }
+
+    /// Transform the normal `n` with the normal matrix and renormalize it, so that it stays
+    /// perpendicular to the transformed surface also under non-uniform scaling. Returns the zero
+    /// vector if the transform is not invertible.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use geometry::transform::Transform;
+    /// use math::{assert_eq_eps, Matrix3f, Vec3f};
+    ///
+    /// let scale = Transform::from(Matrix3f::new(2.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0));
+    /// let xform = Transform::rotation_z(0.3) * scale;
+    ///
+    /// let n = Vec3f::new(1.0, 1.0, 0.0).unit();
+    /// let tangent0 = xform * Vec3f::new(1.0, -1.0, 0.0);
+    /// let tangent1 = xform * Vec3f::new(0.0, 0.0, 1.0);
+    ///
+    /// let transformed = xform.transform_normal(n);
+    /// assert_eq_eps!(transformed.length(), 1.0, 1e-6);
+    /// assert_eq_eps!(transformed.dot(tangent0), 0.0, 1e-6);
+    /// assert_eq_eps!(transformed.dot(tangent1), 0.0, 1e-6);
+    ///
+    /// // Transforming the normal like a direction doesn't keep it perpendicular
+    /// assert!((xform * n).dot(tangent0).abs() > 0.1);
+    /// ```
+    pub fn transform_normal(&self, n: Vec3f) -> Vec3f {
+        match self.normal_matrix() {
+            Some(m) => (m * n).unit(),
+            None => Vec3f::zero(),
+        }
+    }
+
     pub fn rotation_x(theta: f32) -> Self {
         Matrix3f::rotation_x(theta).into()
     }
@@ -32,6 +201,26 @@ impl Transform {
         Matrix3f::rotation(theta, a).into()
     }
 
+    /// The rotation of the unit quaternion `q`
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use geometry::transform::Transform;
+    /// use math::{assert_eq_eps, Quaternion, Vec3f};
+    ///
+    /// let axis = Vec3f::new(0.0, 1.0, 0.0);
+    /// let q = Quaternion::from_axis_angle(axis, 0.4);
+    /// assert_eq_eps!(
+    ///     *Transform::from_quaternion(q).as_matrix(),
+    ///     *Transform::rotation_y(0.4).as_matrix(),
+    ///     1e-6
+    /// );
+    /// ```
+    pub fn from_quaternion(q: Quaternion) -> Self {
+        q.to_matrix3().into()
+    }
+
     pub fn translation(a: Vec3f) -> Self {
         Matrix4f::new(
             1.0,
@@ -54,6 +243,36 @@ impl Transform {
         .into()
     }
 
+    /// Scale by `s.x()` along the x axis etc.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use geometry::transform::Transform;
+    /// use math::Vec3f;
+    ///
+    /// let scale = Transform::scale(Vec3f::new(2.0, 3.0, 4.0));
+    /// assert_eq!(scale * Vec3f::new(1.0, 0.0, 0.0), Vec3f::new(2.0, 0.0, 0.0));
+    /// assert_eq!(scale * Vec3f::new(0.0, 1.0, 0.0), Vec3f::new(0.0, 3.0, 0.0));
+    /// assert_eq!(scale * Vec3f::new(0.0, 0.0, 1.0), Vec3f::new(0.0, 0.0, 4.0));
+    /// assert!(scale.is_affine());
+    /// ```
+    pub fn scale(s: Vec3f) -> Self {
+        Matrix4f::diagonal(s.xyz1()).into()
+    }
+
+    /// # Examples
+    ///
+    /// ```rust
+    /// use geometry::transform::Transform;
+    /// use math::Vec3f;
+    ///
+    /// assert_eq!(Transform::uniform_scale(2.0), Transform::scale(Vec3f::new(2.0, 2.0, 2.0)));
+    /// ```
+    pub fn uniform_scale(s: f32) -> Self {
+        Self::scale(Vec3f::new(s, s, s))
+    }
+
     #[allow(clippy::many_single_char_names)]
     pub fn frustum_projection(fov_y: f32, s: f32, near: f32, far: f32) -> Self {
         let g = 1.0 / (fov_y * 0.5).tan();
@@ -80,6 +299,53 @@ impl Transform {
         .into()
     }
 
+    /// Orthographic projection mapping the box with x in [`left`, `right`], y in [`bottom`,
+    /// `top`] and z in [`near`, `far`] to x and y in [-1, 1] and, like `frustum_projection`,
+    /// depth in [0, 1].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use geometry::transform::Transform;
+    /// use geometry::{Point3f, Point4f};
+    /// use math::assert_eq_eps;
+    ///
+    /// let ortho = Transform::orthographic(-2.0, 6.0, -1.0, 3.0, 1.0, 11.0);
+    /// assert!(ortho.is_affine());
+    ///
+    /// let ndc = |x, y, z| *(ortho * Point3f::new(x, y, z)).as_vector();
+    /// assert_eq_eps!(ndc(-2.0, -1.0, 1.0), *Point4f::new(-1.0, -1.0, 0.0, 1.0).as_vector(), 1e-6);
+    /// assert_eq_eps!(ndc(6.0, -1.0, 1.0), *Point4f::new(1.0, -1.0, 0.0, 1.0).as_vector(), 1e-6);
+    /// assert_eq_eps!(ndc(-2.0, 3.0, 11.0), *Point4f::new(-1.0, 1.0, 1.0, 1.0).as_vector(), 1e-6);
+    /// assert_eq_eps!(ndc(6.0, 3.0, 11.0), *Point4f::new(1.0, 1.0, 1.0, 1.0).as_vector(), 1e-6);
+    /// assert_eq_eps!(ndc(2.0, 1.0, 6.0), *Point4f::new(0.0, 0.0, 0.5, 1.0).as_vector(), 1e-6);
+    /// ```
+    pub fn orthographic(left: f32, right: f32, bottom: f32, top: f32, near: f32, far: f32) -> Self {
+        let width_inv = 1.0 / (right - left);
+        let height_inv = 1.0 / (top - bottom);
+        let depth_inv = 1.0 / (far - near);
+
+        Matrix4f::new(
+            2.0 * width_inv,
+            0.0,
+            0.0,
+            -(right + left) * width_inv,
+            0.0,
+            2.0 * height_inv,
+            0.0,
+            -(top + bottom) * height_inv,
+            0.0,
+            0.0,
+            depth_inv,
+            -near * depth_inv,
+            0.0,
+            0.0,
+            0.0,
+            1.0,
+        )
+        .into()
+    }
+
     pub fn infinite_projection(fov_y: f32, s: f32, near: f32, epsilon: f32) -> Self {
         let g = 1.0 / (fov_y * 0.5).tan();
         let e = 1.0 - epsilon;
@@ -1,5 +1,5 @@
 use crate::point::Point;
-use crate::{Point2, Point2f, Point3, Point3f, Point4f};
+use crate::{Point2, Point2f, Point3, Point3f, Point4f, Ray3f};
 use math::vector::{VecElem, Zero};
 use math::{Vec3, Vec3f, Vec4f, Vector};
 
@@ -18,6 +18,54 @@ impl<'a, T: VecElem, const N: usize> Triangle<'a, T, N> {
             points: [p0, p1, p2],
         }
     }
+
+    /// The average of the three vertices.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use geometry::triangle::*;
+    /// use geometry::{Point2f, Point2i, Point3f};
+    ///
+    /// let p0 = Point2i::new(0, 0);
+    /// let p1 = Point2i::new(2, 0);
+    /// let p2 = Point2i::new(1, 3);
+    /// assert_eq!(Triangle::new(&p0, &p1, &p2).centroid(), Point2f::new(1.0, 1.0));
+    ///
+    /// let p0 = Point3f::new(0.0, 0.0, 3.0);
+    /// let p1 = Point3f::new(3.0, 0.0, 0.0);
+    /// let p2 = Point3f::new(0.0, 3.0, 0.0);
+    /// assert_eq!(Triangle::new(&p0, &p1, &p2).centroid(), Point3f::new(1.0, 1.0, 1.0));
+    /// ```
+    pub fn centroid(&self) -> Point<f32, N> {
+        let sum = self
+            .points
+            .iter()
+            .fold(Vector::zero(), |acc, p| acc + p.as_vector().as_f32());
+        (sum / 3.0).into()
+    }
+}
+
+impl<'a, T: VecElem + PartialOrd, const N: usize> Triangle<'a, T, N> {
+    /// The minimum and maximum corners of the axis aligned box containing the triangle.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use geometry::triangle::*;
+    /// use geometry::Point2i;
+    ///
+    /// let p0 = Point2i::new(1, 5);
+    /// let p1 = Point2i::new(-2, 0);
+    /// let p2 = Point2i::new(4, 2);
+    ///
+    /// let triangle = Triangle::new(&p0, &p1, &p2);
+    /// assert_eq!(triangle.bounding_box(), (Point2i::new(-2, 0), Point2i::new(4, 5)));
+    /// ```
+    pub fn bounding_box(&self) -> (Point<T, N>, Point<T, N>) {
+        let [p0, p1, p2] = self.points.map(|p| *p.as_vector());
+        (p0.min(p1).min(p2).into(), p0.max(p1).max(p2).into())
+    }
 }
 
 impl<'a, T: VecElem> Triangle<'a, T, 3> {
@@ -63,8 +111,24 @@ impl<'a, T: VecElem> Triangle<'a, T, 2> {
     /// assert_eq!(triangle.signed_area_doubled(), -2);
     /// ```
     pub fn signed_area_doubled(&self) -> T {
-        (self.points[2].x() - self.points[0].x()) * (self.points[1].y() - self.points[0].y())
-            - (self.points[1].x() - self.points[0].x()) * (self.points[2].y() - self.points[0].y())
+        (*self.points[2] - *self.points[0]).perp_dot(*self.points[1] - *self.points[0])
+    }
+
+    /// # Examples
+    ///
+    /// ```rust
+    /// use geometry::triangle::*;
+    /// use geometry::Point2i;
+    ///
+    /// let p0 = Point2i::new(0, 0);
+    /// let p1 = Point2i::new(2, 0);
+    /// let p2 = Point2i::new(0, 1);
+    ///
+    /// assert_eq!(Triangle::new(&p0, &p1, &p2).area(), 1.0);
+    /// assert_eq!(Triangle::new(&p0, &p2, &p1).area(), 1.0);
+    /// ```
+    pub fn area(&self) -> f32 {
+        self.signed_area_doubled().as_f32().abs() / 2.0
     }
 }
 
@@ -141,7 +205,7 @@ impl<'a, T: VecElem + PartialOrd> Triangle<'a, T, 2> {
     pub fn interpolate(&self, bary: &Point3f) -> Point2f {
         let mut v = Vector::zero();
         for i in 0..3 {
-            v = v + self.points[i].as_vector().as_f32() * bary[i];
+            v += self.points[i].as_vector().as_f32() * bary[i];
         }
         v.into()
     }
@@ -206,6 +270,71 @@ impl<'a, T: VecElem + PartialOrd> Triangle<'a, T, 3> {
     }
 }
 
+impl<'a> Triangle3f<'a> {
+    /// Intersect `ray` with the triangle using the Möller–Trumbore algorithm, returning the ray
+    /// parameter `t` of the hit and its barycentric coordinates. Both faces of the triangle are
+    /// hit. Returns `None` if the ray misses, is parallel to the triangle or the hit is behind
+    /// the ray's origin.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use geometry::triangle::*;
+    /// use geometry::{Point3f, Ray3f};
+    /// use math::{assert_eq_eps, Vec3f};
+    ///
+    /// let p0 = Point3f::new(0.0, 0.0, 0.0);
+    /// let p1 = Point3f::new(3.0, 0.0, 0.0);
+    /// let p2 = Point3f::new(0.0, 3.0, 0.0);
+    /// let triangle = Triangle::new(&p0, &p1, &p2);
+    ///
+    /// // Toward the centroid, from both sides
+    /// let down = Ray3f::new(Point3f::new(1.0, 1.0, 2.0), Vec3f::new(0.0, 0.0, -1.0));
+    /// let (t, bary) = triangle.intersect_ray(&down).unwrap();
+    /// assert_eq_eps!(t, 2.0, 1e-6);
+    /// assert_eq_eps!(*bary.as_vector(), Vec3f::new(1.0, 1.0, 1.0) / 3.0, 1e-6);
+    /// let up = Ray3f::new(Point3f::new(1.0, 1.0, -4.0), Vec3f::new(0.0, 0.0, 2.0));
+    /// assert_eq_eps!(triangle.intersect_ray(&up).unwrap().0, 2.0, 1e-6);
+    ///
+    /// // Missing, parallel and pointing away
+    /// let miss = Ray3f::new(Point3f::new(2.0, 2.0, 2.0), Vec3f::new(0.0, 0.0, -1.0));
+    /// assert_eq!(triangle.intersect_ray(&miss), None);
+    /// let parallel = Ray3f::new(Point3f::new(-1.0, 1.0, 0.0), Vec3f::new(1.0, 0.0, 0.0));
+    /// assert_eq!(triangle.intersect_ray(&parallel), None);
+    /// let away = Ray3f::new(Point3f::new(1.0, 1.0, 2.0), Vec3f::new(0.0, 0.0, 1.0));
+    /// assert_eq!(triangle.intersect_ray(&away), None);
+    /// ```
+    pub fn intersect_ray(&self, ray: &Ray3f) -> Option<(f32, Point3f)> {
+        let p0 = *self.points[0];
+        let edge1 = *self.points[1] - p0;
+        let edge2 = *self.points[2] - p0;
+
+        let p = ray.dir.cross(edge2);
+        let det = edge1.dot(p);
+        if det.abs() < f32::EPSILON {
+            return None;
+        }
+        let det_inv = 1.0 / det;
+
+        let to_origin = ray.origin - p0;
+        let u = to_origin.dot(p) * det_inv;
+        if !(0.0..=1.0).contains(&u) {
+            return None;
+        }
+        let q = to_origin.cross(edge1);
+        let v = ray.dir.dot(q) * det_inv;
+        if v < 0.0 || u + v > 1.0 {
+            return None;
+        }
+
+        let t = edge2.dot(q) * det_inv;
+        if t < 0.0 {
+            return None;
+        }
+        Some((t, Point3f::new(1.0 - u - v, u, v)))
+    }
+}
+
 impl<'a> Triangle4f<'a> {
     /// # Examples
     ///
@@ -220,9 +349,9 @@ impl<'a> Triangle4f<'a> {
     ///
     /// let triangle = Triangle::new(&p0, &p1, &p2);
     ///
-    /// assert_eq!(triangle.barycentric_coordinates(&p0), Some(Point3f::new(1.0, 0.0, 0.0)));
-    /// assert_eq!(triangle.barycentric_coordinates(&p1), Some(Point3f::new(0.0, 1.0, 0.0)));
-    /// assert_eq!(triangle.barycentric_coordinates(&p2), Some(Point3f::new(0.0, 0.0, 1.0)));
+    /// assert_eq!(triangle.barycentric_coordinates(&p0.xyz()), Some(Point3f::new(1.0, 0.0, 0.0)));
+    /// assert_eq!(triangle.barycentric_coordinates(&p1.xyz()), Some(Point3f::new(0.0, 1.0, 0.0)));
+    /// assert_eq!(triangle.barycentric_coordinates(&p2.xyz()), Some(Point3f::new(0.0, 0.0, 1.0)));
     /// ```
     pub fn barycentric_coordinates(&self, p: &Point3f) -> Option<Point3f> {
         let p0 = self.points[0].xyz();
@@ -264,7 +393,7 @@ impl<'a> Triangle4f<'a> {
     pub fn interpolate(&self, bary: &Point3f) -> Point4f {
         let mut v = Vec4f::zero();
         for i in 0..3 {
-            v = v + *self.points[i].as_vector() * bary[i];
+            v += *self.points[i].as_vector() * bary[i];
         }
         v.into()
     }
@@ -276,6 +405,30 @@ impl<'a> Triangle4f<'a> {
     }
 }
 
+/// Blend three per-vertex attributes (uvs, normals, colors, ...) with the barycentric
+/// coordinates `bary`.
+///
+/// # Examples
+///
+/// ```rust
+/// use geometry::triangle::interpolate_attr;
+/// use geometry::Point3f;
+/// use math::{assert_eq_eps, Vec2f};
+///
+/// let uvs = [Vec2f::new(0.0, 0.0), Vec2f::new(1.0, 0.0), Vec2f::new(0.5, 0.9)];
+/// let third = 1.0 / 3.0;
+/// let centroid = interpolate_attr(uvs, &Point3f::new(third, third, third));
+/// assert_eq_eps!(centroid, Vec2f::new(0.5, 0.3), 0.000001);
+///
+/// assert_eq!(interpolate_attr(uvs, &Point3f::new(0.0, 1.0, 0.0)), uvs[1]);
+/// ```
+pub fn interpolate_attr<const M: usize>(
+    attrs: [Vector<f32, M>; 3],
+    bary: &Point3f,
+) -> Vector<f32, M> {
+    attrs[0] * bary.x() + attrs[1] * bary.y() + attrs[2] * bary.z()
+}
+
 pub type Triangle2<'a, T> = Triangle<'a, T, 2>;
 pub type Triangle2f<'a> = Triangle2<'a, f32>;
 pub type Triangle2i<'a> = Triangle2<'a, i32>;
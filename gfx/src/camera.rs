@@ -14,6 +14,8 @@ pub struct Camera {
     pub xform: Transform,
     /// Projection transform
     pub projection: Transform,
+    /// Does `projection` map near to depth 1 and far to 0 instead of the other way around
+    pub reversed_z: bool,
 }
 
 impl Camera {
@@ -29,7 +31,8 @@ impl Camera {
     ///
     /// let mut camera = Camera {
     ///   xform: Transform::translation(Vec3f::new(0.0, 0.0, 0.0)),
-    ///   projection: Transform::infinite_projection(1.0, 1.0, 0.1, 0.001)
+    ///   projection: Transform::infinite_projection(1.0, 1.0, 0.1, 0.001),
+    ///   reversed_z: false,
     /// };
     ///
     /// camera.look_at(Point3f::new(1.0, 0.0, 0.0));
@@ -47,6 +50,26 @@ impl Camera {
         ));
     }
 
+    /// Rebuild `projection` as a perspective projection with vertical field of view `fov_y` and
+    /// aspect ratio `aspect`. An infinite `far` gives an infinite projection. With `reversed_z`
+    /// near maps to depth 1 and far to 0, which evens out the depth precision.
+    pub fn set_perspective(
+        &mut self,
+        fov_y: f32,
+        aspect: f32,
+        near: f32,
+        far: f32,
+        reversed_z: bool,
+    ) {
+        self.projection = match (far.is_infinite(), reversed_z) {
+            (false, false) => Transform::frustum_projection(fov_y, aspect, near, far),
+            (false, true) => Transform::reverse_frustum_projection(fov_y, aspect, near, far),
+            (true, false) => Transform::infinite_projection(fov_y, aspect, near, 0.0),
+            (true, true) => Transform::rev_infinite_projection(fov_y, aspect, near, 0.0),
+        };
+        self.reversed_z = reversed_z;
+    }
+
     pub fn move_by(&mut self, direction: Vec3f) {
         let new_location = self.location() + direction;
         self.set_location(new_location)
@@ -100,8 +123,35 @@ impl Camera {
         self.xform.as_matrix().col(3).xyz().into()
     }
 
-    /// Return the View transform (World->Camera)
+    /// Return the View transform (World->Camera). Uses the cheaper affine inverse when possible.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use geometry::Point3f;
+    /// use gfx::camera::Camera;
+    /// use geometry::transform::Transform;
+    /// use math::{assert_eq_eps, Vec3f};
+    ///
+    /// let mut camera = Camera {
+    ///   xform: Transform::translation(Vec3f::new(1.0, 2.0, 3.0)),
+    ///   projection: Transform::infinite_projection(1.0, 1.0, 0.1, 0.001),
+    ///   reversed_z: false,
+    /// };
+    /// camera.look_at(Point3f::new(-1.0, 4.0, 0.0));
+    ///
+    /// assert_eq!(camera.view(), camera.xform.inverse_affine());
+    /// assert_eq_eps!(
+    ///     *camera.view().unwrap().as_matrix(),
+    ///     *camera.xform.inverse().unwrap().as_matrix(),
+    ///     0.00001
+    /// );
+    /// ```
     pub fn view(&self) -> Option<Transform> {
-        self.xform.inverse()
+        if self.xform.is_affine() {
+            self.xform.inverse_affine()
+        } else {
+            self.xform.inverse()
+        }
     }
 }
@@ -15,3 +15,86 @@ impl Color {
         Self::rgba(r, g, b, u8::MAX)
     }
 }
+
+/// A color lookup table for indexed images. Indices past the end of the table look up
+/// `default`, black unless set with `with_default`.
+///
+/// # Examples
+///
+/// ```rust
+/// use gfx::color::{Color, Palette};
+///
+/// let red = Color::rgb(255, 0, 0);
+/// let green = Color::rgb(0, 255, 0);
+/// let blue = Color::rgb(0, 0, 255);
+/// let white = Color::rgb(255, 255, 255);
+/// let palette = Palette::new(vec![red, green, blue, white]);
+///
+/// assert_eq!(palette.len(), 4);
+/// assert_eq!(palette.lookup(0), red);
+/// assert_eq!(palette.lookup(3), white);
+/// assert_eq!(palette.lookup(4), Color::rgb(0, 0, 0));
+///
+/// let palette = palette.with_default(Color::rgba(0, 0, 0, 0));
+/// assert_eq!(palette.lookup(255), Color::rgba(0, 0, 0, 0));
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Palette {
+    colors: Vec<Color>,
+    default: Color,
+}
+
+impl Palette {
+    pub fn new(colors: Vec<Color>) -> Self {
+        Self {
+            colors,
+            default: Color::rgb(0, 0, 0),
+        }
+    }
+
+    /// Build a palette from the data of a PNG `PLTE` chunk, i.e. 1 to 256 RGB triples. Returns
+    /// `None` if `data` isn't a valid `PLTE` chunk.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use gfx::color::{Color, Palette};
+    ///
+    /// let palette = Palette::from_plte(&[255, 0, 0, 10, 20, 30]).unwrap();
+    /// assert_eq!(palette.lookup(1), Color::rgb(10, 20, 30));
+    ///
+    /// assert_eq!(Palette::from_plte(&[255, 0]), None);
+    /// assert_eq!(Palette::from_plte(&[]), None);
+    /// ```
+    pub fn from_plte(data: &[u8]) -> Option<Self> {
+        let rgb_triples = data.chunks_exact(3);
+        if data.is_empty() || !rgb_triples.remainder().is_empty() || data.len() > 3 * 256 {
+            return None;
+        }
+        let colors = rgb_triples
+            .map(|rgb| Color::rgb(rgb[0], rgb[1], rgb[2]))
+            .collect();
+        Some(Self::new(colors))
+    }
+
+    /// Use `default` for indices past the end of the table
+    pub fn with_default(mut self, default: Color) -> Self {
+        self.default = default;
+        self
+    }
+
+    pub fn len(&self) -> usize {
+        self.colors.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.colors.is_empty()
+    }
+
+    pub fn lookup(&self, index: u8) -> Color {
+        self.colors
+            .get(index as usize)
+            .copied()
+            .unwrap_or(self.default)
+    }
+}
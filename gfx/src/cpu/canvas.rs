@@ -1,22 +1,86 @@
 use crate::color::Color;
 
+/// The layout of the pixels in a `Canvas` buffer
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PixelFormat {
+    /// Three bytes per pixel, red, green and blue
+    RGB24,
+    /// Four bytes per pixel, red, green, blue and alpha
+    RGBA32,
+}
+
+impl PixelFormat {
+    pub fn bytes_per_pixel(&self) -> usize {
+        match self {
+            PixelFormat::RGB24 => 3,
+            PixelFormat::RGBA32 => 4,
+        }
+    }
+}
+
 pub struct Canvas<'a> {
     pub buffer: &'a mut [u8],
     pub width: u32,
     pub height: u32,
-    // color: ? Assume RGB24 for now?
+    pub format: PixelFormat,
 }
 
 impl<'a> Canvas<'a> {
-    pub fn draw_point(&mut self, x: i32, y: i32, color: Color) {
+    // Index of the first byte of the pixel at `x`, `y`, or `None` if it is outside the canvas
+    fn index(&self, x: i32, y: i32) -> Option<usize> {
         if x < 0 || x >= self.width as i32 || y < 0 || y >= self.height as i32 {
-            return;
+            return None;
         }
-        // Ignore alpha, assume RGB24 for now
-        let idx = (3 * self.width as i32 * y + 3 * x) as usize;
+        Some(self.format.bytes_per_pixel() * (self.width as usize * y as usize + x as usize))
+    }
+
+    /// Set the pixel at `x`, `y` to `color`. Alpha is written only if the format has it.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use gfx::color::Color;
+    /// use gfx::cpu::canvas::{Canvas, PixelFormat};
+    ///
+    /// let mut buffer = vec![0u8; 2 * 2 * 4];
+    /// let mut canvas = Canvas {
+    ///     buffer: &mut buffer,
+    ///     width: 2,
+    ///     height: 2,
+    ///     format: PixelFormat::RGBA32,
+    /// };
+    /// canvas.draw_point(1, 1, Color::rgba(10, 20, 30, 40));
+    /// canvas.draw_point(2, 0, Color::rgb(255, 255, 255));
+    /// assert_eq!(&buffer[12..], &[10, 20, 30, 40]);
+    /// assert!(buffer[..12].iter().all(|&b| b == 0));
+    /// ```
+    pub fn draw_point(&mut self, x: i32, y: i32, color: Color) {
+        let idx = match self.index(x, y) {
+            Some(idx) => idx,
+            None => return,
+        };
 
         self.buffer[idx] = color.r;
         self.buffer[idx + 1] = color.g;
         self.buffer[idx + 2] = color.b;
+        if self.format == PixelFormat::RGBA32 {
+            self.buffer[idx + 3] = color.a;
+        }
+    }
+
+    /// Blend `color` over the current color of the pixel, `alpha` being the weight of `color`.
+    pub fn blend_point(&mut self, x: i32, y: i32, color: Color, alpha: f32) {
+        let idx = match self.index(x, y) {
+            Some(idx) => idx,
+            None => return,
+        };
+
+        let blend = |old: u8, new: u8| (old as f32 + (new as f32 - old as f32) * alpha) as u8;
+        self.buffer[idx] = blend(self.buffer[idx], color.r);
+        self.buffer[idx + 1] = blend(self.buffer[idx + 1], color.g);
+        self.buffer[idx + 2] = blend(self.buffer[idx + 2], color.b);
+        if self.format == PixelFormat::RGBA32 {
+            self.buffer[idx + 3] = blend(self.buffer[idx + 3], color.a);
+        }
     }
 }
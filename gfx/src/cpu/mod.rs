@@ -1,19 +1,40 @@
 pub mod canvas;
 
 use crate::color::Color;
+use crate::light::Light;
 use canvas::Canvas;
-use geometry::line_segment::LineSegment2i;
+use geometry::line_segment::{LineSegment2i, LineSegment3f};
 use geometry::transform::Transform;
-use geometry::triangle::{Triangle2f, Triangle3f, Triangle4f};
-use geometry::{Point3f, Point4f};
+use geometry::triangle::{interpolate_attr, Triangle2f, Triangle3f, Triangle4f};
+use geometry::{Point2i, Point3f, Point4f};
 use loader::obj::Obj;
 use loader::png::Png;
+use math::{Vec2f, Vec3f, Vec4f};
 use std::cmp;
-use std::cmp::Ordering::Equal;
 use std::mem;
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LineAlgorithm {
+    /// Bresenham's line drawing algorithm, every pixel has full coverage
+    Bresenham,
+    /// Xiaolin Wu's antialiased line drawing algorithm, pixels have fractional coverage
+    Wu,
+}
+
+/// Call `plot(x, y, coverage)` for each pixel of `line_segment` as rasterized by `algorithm`.
+pub fn rasterize_line<F: FnMut(i32, i32, f32)>(
+    line_segment: &LineSegment2i,
+    algorithm: LineAlgorithm,
+    plot: F,
+) {
+    match algorithm {
+        LineAlgorithm::Bresenham => rasterize_line_bresenham(line_segment, plot),
+        LineAlgorithm::Wu => rasterize_line_wu(line_segment, plot),
+    }
+}
+
 // Bresenham's line drawing algorithm, ported from ssloy/tinyrenderer
-pub fn draw_line_segment(canvas: &mut Canvas, line_segment: &LineSegment2i, color: Color) {
+fn rasterize_line_bresenham<F: FnMut(i32, i32, f32)>(line_segment: &LineSegment2i, mut plot: F) {
     let mut x0 = line_segment.start.x();
     let mut y0 = line_segment.start.y();
     let mut x1 = line_segment.end.x();
@@ -36,9 +57,9 @@ pub fn draw_line_segment(canvas: &mut Canvas, line_segment: &LineSegment2i, colo
     let mut y = y0;
     for x in x0..=x1 {
         if steep {
-            canvas.draw_point(y, x, color);
+            plot(y, x, 1.0);
         } else {
-            canvas.draw_point(x, y, color);
+            plot(x, y, 1.0);
         }
         error += d_error;
         if error > 0.5 {
@@ -48,21 +69,197 @@ pub fn draw_line_segment(canvas: &mut Canvas, line_segment: &LineSegment2i, colo
     }
 }
 
+// Xiaolin Wu's line drawing algorithm, simplified for integer end points
+fn rasterize_line_wu<F: FnMut(i32, i32, f32)>(line_segment: &LineSegment2i, mut plot: F) {
+    let mut x0 = line_segment.start.x();
+    let mut y0 = line_segment.start.y();
+    let mut x1 = line_segment.end.x();
+    let mut y1 = line_segment.end.y();
+
+    let steep = (x0 - x1).abs() < (y0 - y1).abs();
+    if steep {
+        mem::swap(&mut x0, &mut y0);
+        mem::swap(&mut x1, &mut y1);
+    }
+    if x0 > x1 {
+        mem::swap(&mut x0, &mut x1);
+        mem::swap(&mut y0, &mut y1);
+    }
+    let dx = x1 - x0;
+    let dy = y1 - y0;
+    let gradient = if dx == 0 { 0.0 } else { dy as f32 / dx as f32 };
+
+    let mut plot = |x: i32, y: i32, coverage: f32| {
+        if coverage > 0.0 {
+            if steep {
+                plot(y, x, coverage);
+            } else {
+                plot(x, y, coverage);
+            }
+        }
+    };
+    for x in x0..=x1 {
+        let y = y0 as f32 + gradient * (x - x0) as f32;
+        let y_floor = y.floor();
+        let fraction = y - y_floor;
+        plot(x, y_floor as i32, 1.0 - fraction);
+        plot(x, y_floor as i32 + 1, fraction);
+    }
+}
+
+pub fn draw_line_segment(canvas: &mut Canvas, line_segment: &LineSegment2i, color: Color) {
+    rasterize_line(line_segment, LineAlgorithm::Bresenham, |x, y, _| {
+        canvas.draw_point(x, y, color)
+    });
+}
+
+// Blend from `c0` at `t = 0` to `c1` at `t = 1`
+fn lerp_color(c0: Color, c1: Color, t: f32) -> Color {
+    let channel = |a: u8, b: u8| (a as f32 + (b as f32 - a as f32) * t).round() as u8;
+    Color::rgba(
+        channel(c0.r, c1.r),
+        channel(c0.g, c1.g),
+        channel(c0.b, c1.b),
+        channel(c0.a, c1.a),
+    )
+}
+
+/// Draw a line blending from `c0` at the start to `c1` at the end.
+pub fn draw_line_gradient(canvas: &mut Canvas, line_segment: &LineSegment2i, c0: Color, c1: Color) {
+    let start = line_segment.start;
+    let end = line_segment.end;
+    // The progress along the line by the longer axis, which the walk steps one pixel at a time
+    let (dx, dy) = (end.x() - start.x(), end.y() - start.y());
+    let t = |x: i32, y: i32| {
+        if dx.abs() >= dy.abs() {
+            if dx == 0 {
+                0.0
+            } else {
+                (x - start.x()) as f32 / dx as f32
+            }
+        } else {
+            (y - start.y()) as f32 / dy as f32
+        }
+    };
+    rasterize_line(line_segment, LineAlgorithm::Bresenham, |x, y, _| {
+        canvas.draw_point(x, y, lerp_color(c0, c1, t(x, y)))
+    });
+}
+
+/// Draw an antialiased line, blending `color` over the existing canvas contents by the
+/// coverage of each pixel.
+pub fn draw_line_aa(canvas: &mut Canvas, line_segment: &LineSegment2i, color: Color) {
+    rasterize_line(line_segment, LineAlgorithm::Wu, |x, y, coverage| {
+        canvas.blend_point(x, y, color, coverage)
+    });
+}
+
+// Call `plot(dx, dy)` for the offsets from the center of the pixels of each octant of a circle of
+// `radius`, as given by the midpoint circle algorithm, with 0 <= dy <= dx
+fn rasterize_circle_octant<F: FnMut(i32, i32)>(radius: i32, mut plot: F) {
+    let mut x = radius;
+    let mut y = 0;
+    let mut error = 1 - radius;
+    while x >= y {
+        plot(x, y);
+        y += 1;
+        if error < 0 {
+            error += 2 * y + 1;
+        } else {
+            x -= 1;
+            error += 2 * (y - x) + 1;
+        }
+    }
+}
+
+/// Draw the outline of the circle of `radius` around `center` with the midpoint circle algorithm.
+pub fn draw_circle(canvas: &mut Canvas, center: Point2i, radius: i32, color: Color) {
+    let (cx, cy) = (center.x(), center.y());
+    rasterize_circle_octant(radius, |x, y| {
+        for &(dx, dy) in &[(x, y), (y, x)] {
+            canvas.draw_point(cx + dx, cy + dy, color);
+            canvas.draw_point(cx - dx, cy + dy, color);
+            canvas.draw_point(cx + dx, cy - dy, color);
+            canvas.draw_point(cx - dx, cy - dy, color);
+        }
+    });
+}
+
+// Draw the pixels from `x0` to `x1` on row `y`, ends included, clipped to the canvas
+fn fill_span(canvas: &mut Canvas, x0: i32, x1: i32, y: i32, color: Color) {
+    if y < 0 || y >= canvas.height as i32 {
+        return;
+    }
+    for x in cmp::max(x0, 0)..=cmp::min(x1, canvas.width as i32 - 1) {
+        canvas.draw_point(x, y, color);
+    }
+}
+
+/// Draw the circle of `radius` around `center` filled, covering the same pixels as `draw_circle`
+/// and the ones inside them.
+pub fn fill_circle(canvas: &mut Canvas, center: Point2i, radius: i32, color: Color) {
+    let (cx, cy) = (center.x(), center.y());
+    rasterize_circle_octant(radius, |x, y| {
+        for &(dx, dy) in &[(x, y), (y, x)] {
+            fill_span(canvas, cx - dx, cx + dx, cy + dy, color);
+            fill_span(canvas, cx - dx, cx + dx, cy - dy, color);
+        }
+    });
+}
+
+/// Fill the rectangle with corners `min` and `max`, both included, clipped to the canvas.
+pub fn fill_rect(canvas: &mut Canvas, min: Point2i, max: Point2i, color: Color) {
+    for y in cmp::max(min.y(), 0)..=cmp::min(max.y(), canvas.height as i32 - 1) {
+        fill_span(canvas, min.x(), max.x(), y, color);
+    }
+}
+
 pub struct ZBuffer {
     buf: Vec<f32>,
     width: u32,
     height: u32,
+    // With reversed z near is 1 and far is 0, so larger depths win the depth test
+    reversed_z: bool,
 }
 
 impl ZBuffer {
-    fn new(width: u32, height: u32) -> Self {
+    pub fn new(width: u32, height: u32, reversed_z: bool) -> Self {
         ZBuffer {
-            buf: vec![f32::MAX; (width * height) as usize],
+            buf: vec![Self::farthest(reversed_z); (width * height) as usize],
             width,
             height,
+            reversed_z,
         }
     }
 
+    /// Reset every depth to the farthest possible, e.g. before drawing the next frame
+    pub fn clear(&mut self) {
+        let farthest = Self::farthest(self.reversed_z);
+        self.buf.iter_mut().for_each(|z| *z = farthest);
+    }
+
+    fn farthest(reversed_z: bool) -> f32 {
+        if reversed_z {
+            f32::MIN
+        } else {
+            f32::MAX
+        }
+    }
+
+    /// If `z` is nearer than the current depth at (x, y), store it and return true
+    fn test_and_set(&mut self, x: u32, y: u32, z: f32) -> bool {
+        let current = self.get(x, y);
+        let is_nearer = if self.reversed_z {
+            z > current
+        } else {
+            z < current
+        };
+        if is_nearer {
+            self.set(x, y, z);
+        }
+        is_nearer
+    }
+
     fn set(&mut self, x: u32, y: u32, z: f32) {
         assert!(x < self.width && y < self.height);
         self.buf[(y * self.width + x) as usize] = z;
@@ -74,59 +271,61 @@ impl ZBuffer {
     }
 }
 
-fn interpolate_color_from_texture(
-    texture: &Png,
-    texture_triangle: &Triangle2f,
-    bary: &Point3f,
-) -> Color {
-    let coords = texture_triangle.interpolate(bary);
-    let x = (coords.x() * texture.width as f32).floor() as u32;
-    let y = texture.height - (coords.y() * texture.height as f32).floor() as u32;
-    if x >= texture.width || y >= texture.height {
-        println!("Invalid x or y: {} {}", x, y);
-        return Color::rgb(255, 0, 0);
+/// How texture coordinates outside [0, 1) are mapped onto the texture
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WrapMode {
+    /// Use the texel at the nearest edge
+    Clamp,
+    /// Tile the texture
+    Repeat,
+}
+
+impl WrapMode {
+    // Map the texel index `i` into 0..`size`
+    fn apply(&self, i: i32, size: u32) -> u32 {
+        match self {
+            WrapMode::Clamp => i.clamp(0, size as i32 - 1) as u32,
+            WrapMode::Repeat => i.rem_euclid(size as i32) as u32,
+        }
     }
+}
+
+fn color_from_texture(texture: &Png, coords: Vec2f, wrap_mode: WrapMode) -> Color {
+    let x = (coords.x() * texture.width as f32).floor() as i32;
+    let y = (coords.y() * texture.height as f32).floor() as i32;
+    let x = wrap_mode.apply(x, texture.width);
+    let y = wrap_mode.apply(y, texture.height);
     let i = (texture.bytes_per_pixel as u32 * (texture.width * y + x)) as usize;
     Color::rgb(texture.data[i], texture.data[i + 1], texture.data[i + 2])
 }
 
+// Screen space bounding box of `triangle`, extended by one pixel and clipped to `z_buffer`.
+fn bounding_box(triangle: &Triangle4f, z_buffer: &ZBuffer) -> (i32, i32, i32, i32) {
+    let (min, max) = triangle.bounding_box();
+
+    let min_x = cmp::max(0, min.x().floor() as i32 - 1);
+    let min_y = cmp::max(0, min.y().floor() as i32 - 1);
+    let max_x = cmp::min(z_buffer.width as i32, max.x().ceil() as i32 + 1);
+    let max_y = cmp::min(z_buffer.height as i32, max.y().ceil() as i32 + 1);
+
+    (min_x, min_y, max_x, max_y)
+}
+
+/// Draw `triangle` textured and lit by `light`, which is given in the same space as the normals.
+#[allow(clippy::too_many_arguments)]
 pub fn draw_triangle(
     canvas: &mut Canvas,
     triangle: &Triangle4f,
     normal_triangle: &Triangle3f,
     texture_triangle: &Triangle2f,
     texture: &Png,
+    wrap_mode: WrapMode,
+    light: &Light,
     z_buffer: &mut ZBuffer,
 ) {
-    let min_x = triangle
-        .points
-        .iter()
-        .map(|&p| p.x())
-        .min_by(|a, b| a.partial_cmp(b).unwrap_or(Equal))
-        .unwrap();
-    let min_y = triangle
-        .points
-        .iter()
-        .map(|&p| p.y())
-        .min_by(|a, b| a.partial_cmp(b).unwrap_or(Equal))
-        .unwrap();
-    let max_x = triangle
-        .points
-        .iter()
-        .map(|&p| p.x())
-        .max_by(|a, b| a.partial_cmp(b).unwrap_or(Equal))
-        .unwrap();
-    let max_y = triangle
-        .points
-        .iter()
-        .map(|&p| p.y())
-        .max_by(|a, b| a.partial_cmp(b).unwrap_or(Equal))
-        .unwrap();
-
-    let min_x = cmp::max(0, min_x.floor() as i32 - 1);
-    let min_y = cmp::max(0, min_y.floor() as i32 - 1);
-    let max_x = cmp::min(z_buffer.width as i32, max_x.ceil() as i32 + 1);
-    let max_y = cmp::min(z_buffer.height as i32, max_y.ceil() as i32 + 1);
+    let (min_x, min_y, max_x, max_y) = bounding_box(triangle, z_buffer);
+    let normals = normal_triangle.points.map(|p| *p.as_vector());
+    let uvs = texture_triangle.points.map(|p| *p.as_vector());
 
     for y in min_y..max_y {
         for x in min_x..max_x {
@@ -143,16 +342,9 @@ pub fn draw_triangle(
                         continue;
                     } else {
                         let p = triangle.interpolate(&b);
-                        let n_z = normal_triangle.interpolate(&b).z();
-                        let coeff = n_z * n_z;
-                        let c = interpolate_color_from_texture(texture, texture_triangle, &b);
-                        let c = Color::rgb(
-                            (c.r as f32 * coeff) as u8,
-                            (c.g as f32 * coeff) as u8,
-                            (c.b as f32 * coeff) as u8,
-                        );
-                        if z_buffer.get(x as u32, y as u32) > p.z() {
-                            z_buffer.set(x as u32, y as u32, p.z());
+                        let c = color_from_texture(texture, interpolate_attr(uvs, &b), wrap_mode);
+                        let c = light.shade(c, interpolate_attr(normals, &b));
+                        if z_buffer.test_and_set(x as u32, y as u32, p.z()) {
                             canvas.draw_point(x, y, c);
                         }
                     }
@@ -162,63 +354,801 @@ pub fn draw_triangle(
     }
 }
 
+fn interpolate_vertex_colors(colors: &[Color; 3], bary: &Point3f) -> Color {
+    let channel = |f: fn(&Color) -> u8| {
+        let c = f(&colors[0]) as f32 * bary.x()
+            + f(&colors[1]) as f32 * bary.y()
+            + f(&colors[2]) as f32 * bary.z();
+        c.round().clamp(0.0, 255.0) as u8
+    };
+    Color::rgba(
+        channel(|c| c.r),
+        channel(|c| c.g),
+        channel(|c| c.b),
+        channel(|c| c.a),
+    )
+}
+
+/// Draw `triangle` blending the per-vertex `colors` with perspective corrected barycentric
+/// coordinates.
+pub fn draw_triangle_vertex_colors(
+    canvas: &mut Canvas,
+    triangle: &Triangle4f,
+    colors: [Color; 3],
+    z_buffer: &mut ZBuffer,
+) {
+    let (min_x, min_y, max_x, max_y) = bounding_box(triangle, z_buffer);
+
+    for y in min_y..max_y {
+        for x in min_x..max_x {
+            let p = Point3f::new(x as f32, y as f32, 0.0);
+
+            match triangle.pc_barycentric_coordinates(&p) {
+                Some(b) if b.x() >= 0.0 && b.y() >= 0.0 && b.z() >= 0.0 => {
+                    let p = triangle.interpolate(&b);
+                    if z_buffer.test_and_set(x as u32, y as u32, p.z()) {
+                        canvas.draw_point(x, y, interpolate_vertex_colors(&colors, &b));
+                    }
+                }
+                _ => continue,
+            }
+        }
+    }
+}
+
+// Map a perspective divided point from normalized device coordinates onto a `width` x `height`
+// canvas, y pointing down
+fn viewport_transform(p: &Point4f, width: u32, height: u32) -> Point4f {
+    Point4f::new(
+        (p.x() + 1.0) * width as f32 / 2.0,
+        height as f32 - ((p.y() + 1.0) * height as f32 / 2.0),
+        p.z(),
+        p.w(),
+    )
+}
+
+// Signed distance-like measure of the clip space point `v` from the near plane, non-negative in
+// front of it. Without reversed z the near plane is at z = 0, with it at z = w, which keeps
+// points with w <= 0 behind the near plane for all the perspective projections of `Transform`.
+fn near_distance(v: &Vec4f, reversed_z: bool) -> f32 {
+    if reversed_z {
+        v.w() - v.z()
+    } else {
+        v.z()
+    }
+}
+
+/// Project `line_segment` onto a `width` x `height` canvas with the view-projection transform
+/// `view_projection`, clipping it against the near plane (z = 0 in clip space, or z = w with
+/// `reversed_z`). Returns the screen space end points, ready for `draw_line_segment`, or `None`
+/// if the whole segment is behind the near plane.
+pub fn project_line_segment(
+    line_segment: &LineSegment3f,
+    view_projection: Transform,
+    width: u32,
+    height: u32,
+    reversed_z: bool,
+) -> Option<(Point2i, Point2i)> {
+    let start = *(view_projection * *line_segment.start).as_vec4f();
+    let end = *(view_projection * *line_segment.end).as_vec4f();
+
+    let (d0, d1) = (
+        near_distance(&start, reversed_z),
+        near_distance(&end, reversed_z),
+    );
+    if d0 < 0.0 && d1 < 0.0 {
+        return None;
+    }
+    let clip = |t: f32| start + (end - start) * t;
+    let start = if d0 < 0.0 {
+        clip(d0 / (d0 - d1))
+    } else {
+        start
+    };
+    let end = if d1 < 0.0 { clip(d0 / (d0 - d1)) } else { end };
+
+    let to_screen = |v| {
+        let p = viewport_transform(&Point4f::from(v).perspective_divide(), width, height);
+        Point2i::new(p.x().floor() as i32, p.y().floor() as i32)
+    };
+    Some((to_screen(start), to_screen(end)))
+}
+
+/// Which triangles `draw_obj` skips by their winding order on screen
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CullMode {
+    /// Draw all triangles, for two-sided geometry
+    None,
+    /// Skip the triangles going around clockwise on screen, i.e. the back faces of models
+    /// authored counterclockwise
+    Back,
+    /// Skip the triangles going around counterclockwise on screen, for models authored clockwise
+    Front,
+}
+
+impl CullMode {
+    // Is a triangle with the screen space normal z coordinate `normal_z` skipped. With y pointing
+    // down counterclockwise triangles have a non-positive normal z.
+    fn culls(&self, normal_z: f32) -> bool {
+        match self {
+            CullMode::None => false,
+            CullMode::Back => normal_z > 0.0,
+            CullMode::Front => normal_z < 0.0,
+        }
+    }
+}
+
+// A vertex in clip space, i.e. before the perspective divide, with the attributes interpolated
+// along with the position when clipping
+#[derive(Clone, Copy, Debug)]
+struct ClipVertex {
+    position: Vec4f,
+    normal: Vec3f,
+    uv: Vec2f,
+}
+
+impl ClipVertex {
+    fn lerp(&self, other: &ClipVertex, t: f32) -> ClipVertex {
+        ClipVertex {
+            position: self.position.lerp(other.position, t),
+            normal: self.normal.lerp(other.normal, t),
+            uv: self.uv.lerp(other.uv, t),
+        }
+    }
+}
+
+// Clip `triangle` against the near plane like `project_line_segment`, calling `emit` with the at
+// most two triangles covering the part in front of it. Vertices behind the near plane, w <= 0 in
+// particular, are never emitted. The winding order is kept.
+fn clip_near<F: FnMut([ClipVertex; 3])>(triangle: [ClipVertex; 3], reversed_z: bool, mut emit: F) {
+    let distance = |v: &ClipVertex| near_distance(&v.position, reversed_z);
+    let in_front = |v: &ClipVertex| distance(v) >= 0.0;
+    match triangle.iter().filter(|v| in_front(v)).count() {
+        0 => {}
+        3 => emit(triangle),
+        _ => {
+            // One vertex in front gives a triangle, two give a quadrilateral
+            let mut polygon = [triangle[0]; 4];
+            let mut len = 0;
+            for (i, a) in triangle.iter().enumerate() {
+                let b = &triangle[(i + 1) % 3];
+                if in_front(a) {
+                    polygon[len] = *a;
+                    len += 1;
+                }
+                if in_front(a) != in_front(b) {
+                    let (d_a, d_b) = (distance(a), distance(b));
+                    polygon[len] = a.lerp(b, d_a / (d_a - d_b));
+                    len += 1;
+                }
+            }
+            for i in 1..len - 1 {
+                emit([polygon[0], polygon[i], polygon[i + 1]]);
+            }
+        }
+    }
+}
+
+/// Draw `obj` textured and lit by `light`, whose direction is given in world space.
+#[allow(clippy::too_many_arguments)]
 pub fn draw_obj(
     canvas: &mut Canvas,
     obj: &Obj,
     texture: &Png,
+    wrap_mode: WrapMode,
+    light: &Light,
     view_xform: Transform,
     projection_xform: Transform,
+    z_buffer: &mut ZBuffer,
+    cull_mode: CullMode,
 ) {
+    let normal_xform = view_xform;
     let view_xform = projection_xform * view_xform;
+    let light = Light {
+        direction: normal_xform * light.direction,
+        ..*light
+    };
     let width = canvas.width;
     let height = canvas.height;
 
-    let mut z_buffer = ZBuffer::new(width, height);
+    let vertex = |v: u32, n: u32, t: u32| ClipVertex {
+        position: *(view_xform * Point3f::from(obj.vertices[v as usize])).as_vec4f(),
+        normal: normal_xform.transform_normal(obj.normals[n as usize]),
+        uv: obj.uvs[t as usize],
+    };
 
     for i in 0..obj.vertex_index_triples.len() {
         let v_indices = &obj.vertex_index_triples[i];
         let t_indices = &obj.uv_index_triples[i];
         let n_indices = &obj.normal_index_triples[i];
 
-        let v0 = view_xform * Point3f::from(obj.vertices[v_indices.0 as usize]);
-        let v0 = v0.perspective_divide();
-        // Project the 3D points onto the canvas, orthographic projection
-        let p0 = Point4f::new(
-            (v0.x() + 1.0) * width as f32 / 2.0,
-            height as f32 - ((v0.y() + 1.0) * height as f32 / 2.0),
-            v0.z(),
-            v0.w(),
+        let triangle = [
+            vertex(v_indices.0, n_indices.0, t_indices.0),
+            vertex(v_indices.1, n_indices.1, t_indices.1),
+            vertex(v_indices.2, n_indices.2, t_indices.2),
+        ];
+        clip_near(triangle, z_buffer.reversed_z, |clipped| {
+            let [p0, p1, p2] = clipped.map(|v| {
+                viewport_transform(
+                    &Point4f::from(v.position).perspective_divide(),
+                    width,
+                    height,
+                )
+            });
+            let f = Triangle4f::new(&p0, &p1, &p2);
+
+            if !cull_mode.culls(f.normal().z()) {
+                let [n0, n1, n2] = clipped.map(|v| Point3f::from(v.normal));
+                let n = Triangle3f::new(&n0, &n1, &n2);
+
+                let [t0, t1, t2] = clipped.map(|v| v.uv.into());
+                let t = Triangle2f::new(&t0, &t1, &t2);
+                draw_triangle(canvas, &f, &n, &t, texture, wrap_mode, &light, z_buffer);
+            }
+        });
+    }
+}
+
+/// Draw each edge of the faces of `obj` once with `draw_line_segment`, projecting and clipping it
+/// like `project_line_segment`. Like in `draw_obj`, `view_xform` can include an object transform,
+/// so this takes the transforms instead of a `Camera`. Without a `ZBuffer` to ask, `reversed_z`
+/// tells if `projection_xform` maps near to depth 1, which puts the near plane at clip space
+/// `z = w` instead of `z = 0`.
+pub fn draw_obj_wireframe(
+    canvas: &mut Canvas,
+    obj: &Obj,
+    view_xform: Transform,
+    projection_xform: Transform,
+    reversed_z: bool,
+    color: Color,
+) {
+    let view_projection = projection_xform * view_xform;
+    let width = canvas.width;
+    let height = canvas.height;
+
+    for (i0, i1) in obj.unique_edges() {
+        let p0 = Point3f::from(obj.vertices[i0 as usize]);
+        let p1 = Point3f::from(obj.vertices[i1 as usize]);
+        let edge = LineSegment3f::new(&p0, &p1);
+        if let Some((start, end)) =
+            project_line_segment(&edge, view_projection, width, height, reversed_z)
+        {
+            draw_line_segment(canvas, &LineSegment2i::new(&start, &end), color);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::camera::Camera;
+    use canvas::PixelFormat;
+    use loader::png::{BitDepth, ColorType};
+
+    fn pixel(buffer: &[u8], width: u32, x: u32, y: u32) -> Color {
+        let idx = (3 * (width * y + x)) as usize;
+        Color::rgb(buffer[idx], buffer[idx + 1], buffer[idx + 2])
+    }
+
+    fn rasterize(line_segment: &LineSegment2i, algorithm: LineAlgorithm) -> Vec<(i32, i32, f32)> {
+        let mut points = Vec::new();
+        rasterize_line(line_segment, algorithm, |x, y, coverage| {
+            points.push((x, y, coverage))
+        });
+        points
+    }
+
+    #[test]
+    fn test_rasterize_line_bresenham() {
+        let start = Point2i::new(0, 0);
+        let end = Point2i::new(4, 4);
+        let diagonal: Vec<(i32, i32, f32)> = (0..=4).map(|i| (i, i, 1.0)).collect();
+        assert_eq!(
+            rasterize(&LineSegment2i::new(&start, &end), LineAlgorithm::Bresenham),
+            diagonal
         );
-        let v1 = view_xform * Point3f::from(obj.vertices[v_indices.1 as usize]);
-        let v1 = v1.perspective_divide();
-        let p1 = Point4f::new(
-            (v1.x() + 1.0) * width as f32 / 2.0,
-            height as f32 - ((v1.y() + 1.0) * height as f32 / 2.0),
-            v1.z(),
-            v1.w(),
+        assert_eq!(
+            rasterize(&LineSegment2i::new(&end, &start), LineAlgorithm::Bresenham),
+            diagonal
         );
-        let v2 = view_xform * Point3f::from(obj.vertices[v_indices.2 as usize]);
-        let v2 = v2.perspective_divide();
-        let p2 = Point4f::new(
-            (v2.x() + 1.0) * width as f32 / 2.0,
-            height as f32 - ((v2.y() + 1.0) * height as f32 / 2.0),
-            v2.z(),
-            v2.w(),
+
+        let end = Point2i::new(0, 3);
+        assert_eq!(
+            rasterize(&LineSegment2i::new(&start, &end), LineAlgorithm::Bresenham),
+            vec![(0, 0, 1.0), (0, 1, 1.0), (0, 2, 1.0), (0, 3, 1.0)]
         );
+    }
 
-        let f = Triangle4f::new(&p0, &p1, &p2);
+    #[test]
+    fn test_rasterize_line_wu() {
+        let start = Point2i::new(0, 0);
+        let end = Point2i::new(4, 2);
+        assert_eq!(
+            rasterize(&LineSegment2i::new(&start, &end), LineAlgorithm::Wu),
+            vec![
+                (0, 0, 1.0),
+                (1, 0, 0.5),
+                (1, 1, 0.5),
+                (2, 1, 1.0),
+                (3, 1, 0.5),
+                (3, 2, 0.5),
+                (4, 2, 1.0)
+            ]
+        );
+    }
 
-        if f.normal().z() <= 0.0 {
-            let n0 = Point3f::from(view_xform * obj.normals[n_indices.0 as usize]);
-            let n1 = Point3f::from(view_xform * obj.normals[n_indices.1 as usize]);
-            let n2 = Point3f::from(view_xform * obj.normals[n_indices.2 as usize]);
-            let n = Triangle3f::new(&n0, &n1, &n2);
+    #[test]
+    fn test_project_line_segment() {
+        let projection = Transform::frustum_projection(std::f32::consts::FRAC_PI_2, 1.0, 1.0, 10.0);
+
+        let start = Point3f::new(0.0, 0.0, 2.0);
+        let end = Point3f::new(1.0, 1.0, 2.0);
+        assert_eq!(
+            project_line_segment(&LineSegment3f::new(&start, &end), projection, 10, 10, false),
+            Some((Point2i::new(5, 5), Point2i::new(7, 2)))
+        );
 
-            let t0 = obj.uvs[t_indices.0 as usize].into();
-            let t1 = obj.uvs[t_indices.1 as usize].into();
-            let t2 = obj.uvs[t_indices.2 as usize].into();
-            let t = Triangle2f::new(&t0, &t1, &t2);
-            draw_triangle(canvas, &f, &n, &t, texture, &mut z_buffer);
+        // Clipped against the near plane at z = 1
+        let start = Point3f::new(1.0, 1.0, 2.0);
+        let end = Point3f::new(1.0, 1.0, 0.0);
+        assert_eq!(
+            project_line_segment(&LineSegment3f::new(&start, &end), projection, 10, 10, false),
+            Some((Point2i::new(7, 2), Point2i::new(10, 0)))
+        );
+
+        let start = Point3f::new(0.0, 0.0, 0.5);
+        let end = Point3f::new(1.0, 1.0, 0.5);
+        assert_eq!(
+            project_line_segment(&LineSegment3f::new(&start, &end), projection, 10, 10, false),
+            None
+        );
+
+        // The same near plane with reversed z, where z >= 0 also behind the camera
+        let reversed =
+            Transform::reverse_frustum_projection(std::f32::consts::FRAC_PI_2, 1.0, 1.0, 10.0);
+        let start = Point3f::new(0.5, 0.5, 2.0);
+        let end = Point3f::new(0.5, 0.5, -2.0);
+        assert_eq!(
+            project_line_segment(&LineSegment3f::new(&start, &end), reversed, 10, 10, true),
+            Some((Point2i::new(6, 3), Point2i::new(7, 2)))
+        );
+    }
+
+    #[test]
+    fn test_draw_triangle_vertex_colors() {
+        let (width, height) = (12, 12);
+        let mut buffer = vec![0u8; (3 * width * height) as usize];
+        let mut canvas = Canvas {
+            buffer: &mut buffer,
+            width,
+            height,
+            format: PixelFormat::RGB24,
+        };
+        let mut z_buffer = ZBuffer::new(width, height, false);
+
+        let p0 = Point4f::new(0.0, 0.0, 0.5, 1.0);
+        let p1 = Point4f::new(9.0, 0.0, 0.5, 1.0);
+        let p2 = Point4f::new(0.0, 9.0, 0.5, 1.0);
+        let triangle = Triangle4f::new(&p0, &p1, &p2);
+        let red = Color::rgb(255, 0, 0);
+        let green = Color::rgb(0, 255, 0);
+        let blue = Color::rgb(0, 0, 255);
+
+        draw_triangle_vertex_colors(&mut canvas, &triangle, [red, green, blue], &mut z_buffer);
+
+        assert_eq!(pixel(&buffer, width, 0, 0), red);
+        assert_eq!(pixel(&buffer, width, 9, 0), green);
+        assert_eq!(pixel(&buffer, width, 0, 9), blue);
+        assert_eq!(pixel(&buffer, width, 3, 3), Color::rgb(85, 85, 85));
+    }
+
+    #[test]
+    fn test_reversed_z() {
+        let (width, height) = (10, 10);
+        let red = Color::rgb(255, 0, 0);
+        let blue = Color::rgb(0, 0, 255);
+        let near = [
+            Point3f::new(-1.0, -1.0, 2.0),
+            Point3f::new(1.0, -1.0, 2.0),
+            Point3f::new(0.0, 1.0, 2.0),
+        ];
+        let far = [
+            Point3f::new(-4.0, -4.0, 4.0),
+            Point3f::new(4.0, -4.0, 4.0),
+            Point3f::new(0.0, 4.0, 4.0),
+        ];
+
+        for &reversed_z in &[false, true] {
+            let mut camera = Camera {
+                xform: Transform::translation(Vec3f::new(0.0, 0.0, 0.0)),
+                projection: Transform::translation(Vec3f::new(0.0, 0.0, 0.0)),
+                reversed_z: false,
+            };
+            camera.set_perspective(std::f32::consts::FRAC_PI_2, 1.0, 1.0, 10.0, reversed_z);
+            let view_projection = camera.projection * camera.view().unwrap();
+            let project = |p: Point3f| {
+                viewport_transform(&(view_projection * p).perspective_divide(), width, height)
+            };
+            let near_depth = project(near[0]).z();
+            let far_depth = project(far[0]).z();
+            assert_eq!(near_depth > far_depth, reversed_z);
+
+            for order in &[[(&near, red), (&far, blue)], [(&far, blue), (&near, red)]] {
+                let mut buffer = vec![0u8; (3 * width * height) as usize];
+                let mut canvas = Canvas {
+                    buffer: &mut buffer,
+                    width,
+                    height,
+                    format: PixelFormat::RGB24,
+                };
+                let mut z_buffer = ZBuffer::new(width, height, camera.reversed_z);
+                for (vertices, color) in order {
+                    let p0 = project(vertices[0]);
+                    let p1 = project(vertices[1]);
+                    let p2 = project(vertices[2]);
+                    let triangle = Triangle4f::new(&p0, &p1, &p2);
+                    draw_triangle_vertex_colors(&mut canvas, &triangle, [*color; 3], &mut z_buffer);
+                }
+                assert_eq!(pixel(&buffer, width, 5, 5), red);
+                assert_eq!(pixel(&buffer, width, 1, 8), blue);
+            }
+        }
+    }
+
+    #[test]
+    fn test_clip_near() {
+        let (width, height) = (20, 20);
+        let fov = std::f32::consts::FRAC_PI_2;
+        // All with the near plane at z = 1
+        let projections = [
+            (Transform::frustum_projection(fov, 1.0, 1.0, 10.0), false),
+            (Transform::infinite_projection(fov, 1.0, 1.0, 0.0), false),
+            (
+                Transform::reverse_frustum_projection(fov, 1.0, 1.0, 10.0),
+                true,
+            ),
+            (Transform::rev_infinite_projection(fov, 1.0, 1.0, 0.0), true),
+        ];
+        let to_screen = |v: &ClipVertex| {
+            viewport_transform(
+                &Point4f::from(v.position).perspective_divide(),
+                width,
+                height,
+            )
+        };
+        let in_viewport = |p: &Point4f| {
+            (0.0..=width as f32).contains(&p.x()) && (0.0..=height as f32).contains(&p.y())
+        };
+
+        for (projection, reversed_z) in projections {
+            let vertex = |p: Point3f| ClipVertex {
+                position: *(projection * p).as_vec4f(),
+                normal: Vec3f::new(0.0, 0.0, -1.0),
+                uv: Vec2f::new(0.0, 0.0),
+            };
+
+            // Straddling the near plane, one vertex behind the camera
+            let behind = vertex(Point3f::new(0.0, 1.5, -1.0));
+            let left = vertex(Point3f::new(-1.0, -0.5, 3.0));
+            let right = vertex(Point3f::new(1.0, -0.5, 3.0));
+            assert!(behind.position.w() < 0.0);
+            assert!(!in_viewport(&to_screen(&behind)));
+
+            let mut clipped = Vec::new();
+            clip_near([behind, left, right], reversed_z, |triangle| {
+                clipped.push(triangle)
+            });
+            assert_eq!(clipped.len(), 2, "reversed z: {}", reversed_z);
+            for v in clipped.iter().flatten() {
+                assert!(v.position.w() > 0.999);
+                assert!(in_viewport(&to_screen(v)));
+            }
+
+            // Two vertices behind
+            let far_behind = vertex(Point3f::new(1.0, 0.5, -1.0));
+            let mut clipped = Vec::new();
+            clip_near([behind, left, far_behind], reversed_z, |triangle| {
+                clipped.push(triangle)
+            });
+            assert_eq!(clipped.len(), 1);
+            assert!(clipped[0].iter().all(|v| in_viewport(&to_screen(v))));
+
+            // Between the camera and the near plane, and in front of it
+            let mut count = 0;
+            let too_near = vertex(Point3f::new(0.0, 0.0, 0.5));
+            clip_near([behind, far_behind, too_near], reversed_z, |_| count += 1);
+            assert_eq!(count, 0);
+            let in_front = vertex(Point3f::new(0.0, 1.0, 2.0));
+            clip_near([left, right, in_front], reversed_z, |_| count += 1);
+            assert_eq!(count, 1);
+        }
+    }
+
+    // Shining forward from the camera, fully lighting the surfaces facing it
+    fn headlight() -> Light {
+        Light {
+            direction: Vec3f::new(0.0, 0.0, 1.0),
+            color: Color::rgb(255, 255, 255),
+            ambient: 0.0,
+        }
+    }
+
+    fn white_texture() -> Png {
+        Png {
+            width: 1,
+            height: 1,
+            bit_depth: BitDepth::Bits8,
+            color_type: ColorType::RGB,
+            bytes_per_pixel: 3,
+            data: vec![255, 255, 255],
+        }
+    }
+
+    #[test]
+    fn test_draw_obj_near_plane() {
+        let (width, height) = (20, 20);
+        let mut buffer = vec![0u8; (3 * width * height) as usize];
+        let mut canvas = Canvas {
+            buffer: &mut buffer,
+            width,
+            height,
+            format: PixelFormat::RGB24,
+        };
+        let mut z_buffer = ZBuffer::new(width, height, false);
+        let obj = Obj {
+            vertices: vec![
+                Vec3f::new(0.0, 0.5, -1.0),
+                Vec3f::new(-1.0, -0.5, 3.0),
+                Vec3f::new(1.0, -0.5, 3.0),
+            ],
+            uvs: vec![Vec2f::new(0.0, 0.0)],
+            normals: vec![Vec3f::new(0.0, 0.0, -1.0)],
+            vertex_index_triples: vec![(0, 1, 2)],
+            uv_index_triples: vec![(0, 0, 0)],
+            normal_index_triples: vec![(0, 0, 0)],
+        };
+        let view = Transform::translation(Vec3f::new(0.0, 0.0, 0.0));
+        let projection = Transform::frustum_projection(std::f32::consts::FRAC_PI_2, 1.0, 1.0, 10.0);
+
+        draw_obj(
+            &mut canvas,
+            &obj,
+            &white_texture(),
+            WrapMode::Clamp,
+            &headlight(),
+            view,
+            projection,
+            &mut z_buffer,
+            CullMode::Back,
+        );
+
+        let white = Color::rgb(255, 255, 255);
+        let black = Color::rgb(0, 0, 0);
+        assert_eq!(pixel(&buffer, width, 10, 11), white);
+        // The part behind the near plane would be drawn above the center
+        assert_eq!(pixel(&buffer, width, 10, 8), black);
+        assert_eq!(pixel(&buffer, width, 10, 2), black);
+    }
+
+    // The pixels of a `width` x `height` canvas set by `draw`
+    fn drawn_pixels<F: FnOnce(&mut Canvas)>(width: u32, height: u32, draw: F) -> Vec<(u32, u32)> {
+        let mut buffer = vec![0u8; (3 * width * height) as usize];
+        let mut canvas = Canvas {
+            buffer: &mut buffer,
+            width,
+            height,
+            format: PixelFormat::RGB24,
+        };
+        draw(&mut canvas);
+        let mut pixels = Vec::new();
+        for y in 0..height {
+            for x in 0..width {
+                if pixel(&buffer, width, x, y) != Color::rgb(0, 0, 0) {
+                    pixels.push((x, y));
+                }
+            }
         }
+        pixels
+    }
+
+    #[test]
+    fn test_draw_circle() {
+        let white = Color::rgb(255, 255, 255);
+        let circle = drawn_pixels(5, 5, |canvas| {
+            draw_circle(canvas, Point2i::new(2, 2), 2, white)
+        });
+        #[rustfmt::skip]
+        assert_eq!(
+            circle,
+            vec![
+                        (1, 0), (2, 0), (3, 0),
+                (0, 1),                         (4, 1),
+                (0, 2),                         (4, 2),
+                (0, 3),                         (4, 3),
+                        (1, 4), (2, 4), (3, 4),
+            ]
+        );
+
+        let filled = drawn_pixels(5, 5, |canvas| {
+            fill_circle(canvas, Point2i::new(2, 2), 2, white)
+        });
+        // The 5 x 5 square without its corners
+        let expected: Vec<(u32, u32)> = (0..5)
+            .flat_map(|y| (0..5).map(move |x| (x, y)))
+            .filter(|&(x, y)| x % 4 != 0 || y % 4 != 0)
+            .collect();
+        assert_eq!(filled, expected);
+
+        // Clipped to the canvas
+        let clipped = drawn_pixels(3, 3, |canvas| {
+            fill_circle(canvas, Point2i::new(0, 0), 2, white)
+        });
+        assert_eq!(
+            clipped,
+            vec![
+                (0, 0),
+                (1, 0),
+                (2, 0),
+                (0, 1),
+                (1, 1),
+                (2, 1),
+                (0, 2),
+                (1, 2)
+            ]
+        );
+        assert_eq!(
+            drawn_pixels(3, 3, |canvas| draw_circle(
+                canvas,
+                Point2i::new(1, 1),
+                0,
+                white
+            )),
+            vec![(1, 1)]
+        );
+    }
+
+    #[test]
+    fn test_fill_rect() {
+        let white = Color::rgb(255, 255, 255);
+        assert_eq!(
+            drawn_pixels(4, 4, |canvas| {
+                fill_rect(canvas, Point2i::new(1, 2), Point2i::new(2, 3), white)
+            }),
+            vec![(1, 2), (2, 2), (1, 3), (2, 3)]
+        );
+        assert_eq!(
+            drawn_pixels(3, 3, |canvas| {
+                fill_rect(canvas, Point2i::new(-5, 1), Point2i::new(5, 9), white)
+            }),
+            vec![(0, 1), (1, 1), (2, 1), (0, 2), (1, 2), (2, 2)]
+        );
+    }
+
+    #[test]
+    fn test_draw_line_gradient() {
+        let (width, height) = (11, 3);
+        let mut buffer = vec![0u8; (3 * width * height) as usize];
+        let mut canvas = Canvas {
+            buffer: &mut buffer,
+            width,
+            height,
+            format: PixelFormat::RGB24,
+        };
+        let black = Color::rgb(0, 0, 0);
+        let white = Color::rgb(255, 255, 255);
+        let start = Point2i::new(10, 2);
+        let end = Point2i::new(0, 0);
+
+        draw_line_gradient(&mut canvas, &LineSegment2i::new(&start, &end), white, black);
+
+        assert_eq!(pixel(&buffer, width, 10, 2), white);
+        assert_eq!(pixel(&buffer, width, 5, 1), Color::rgb(128, 128, 128));
+        assert_eq!(pixel(&buffer, width, 0, 0), black);
+        assert_eq!(pixel(&buffer, width, 8, 2), Color::rgb(204, 204, 204));
+    }
+
+    #[test]
+    fn test_draw_obj_wireframe() {
+        let (width, height) = (20, 20);
+        let white = Color::rgb(255, 255, 255);
+        let obj = Obj {
+            vertices: vec![
+                Vec3f::new(-1.0, -1.0, 2.0),
+                Vec3f::new(1.0, -1.0, 2.0),
+                Vec3f::new(0.0, 1.0, 2.0),
+            ],
+            vertex_index_triples: vec![(0, 1, 2)],
+            ..Default::default()
+        };
+        let view = Transform::translation(Vec3f::new(0.0, 0.0, 0.0));
+        let projection = Transform::frustum_projection(std::f32::consts::FRAC_PI_2, 1.0, 1.0, 10.0);
+
+        let wireframe = drawn_pixels(width, height, |canvas| {
+            draw_obj_wireframe(canvas, &obj, view, projection, false, white)
+        });
+        let (a, b, c) = (
+            Point2i::new(5, 15),
+            Point2i::new(15, 15),
+            Point2i::new(10, 5),
+        );
+        let edges = drawn_pixels(width, height, |canvas| {
+            draw_line_segment(canvas, &LineSegment2i::new(&a, &b), white);
+            draw_line_segment(canvas, &LineSegment2i::new(&b, &c), white);
+            draw_line_segment(canvas, &LineSegment2i::new(&c, &a), white);
+        });
+        assert_eq!(wireframe, edges);
+        assert!(wireframe.contains(&(10, 15)));
+        assert!(!wireframe.contains(&(10, 12)));
+    }
+
+    #[test]
+    fn test_cull_mode() {
+        let (width, height) = (20, 20);
+        // A square split into two triangles wound in opposite directions
+        let obj = Obj {
+            vertices: vec![
+                Vec3f::new(-1.0, -1.0, 2.0),
+                Vec3f::new(1.0, -1.0, 2.0),
+                Vec3f::new(1.0, 1.0, 2.0),
+                Vec3f::new(-1.0, 1.0, 2.0),
+            ],
+            uvs: vec![Vec2f::new(0.0, 0.0)],
+            normals: vec![Vec3f::new(0.0, 0.0, -1.0)],
+            vertex_index_triples: vec![(0, 1, 2), (0, 3, 2)],
+            uv_index_triples: vec![(0, 0, 0); 2],
+            normal_index_triples: vec![(0, 0, 0); 2],
+        };
+        let view = Transform::translation(Vec3f::new(0.0, 0.0, 0.0));
+        let projection = Transform::frustum_projection(std::f32::consts::FRAC_PI_2, 1.0, 1.0, 10.0);
+        let draw = |cull_mode| {
+            drawn_pixels(width, height, |canvas| {
+                let mut z_buffer = ZBuffer::new(width, height, false);
+                let texture = white_texture();
+                draw_obj(
+                    canvas,
+                    &obj,
+                    &texture,
+                    WrapMode::Clamp,
+                    &headlight(),
+                    view,
+                    projection,
+                    &mut z_buffer,
+                    cull_mode,
+                );
+            })
+        };
+        let (lower_right, upper_left) = ((12, 12), (7, 7));
+
+        let both = draw(CullMode::None);
+        assert!(both.contains(&lower_right) && both.contains(&upper_left));
+        let back_culled = draw(CullMode::Back);
+        let front_culled = draw(CullMode::Front);
+        assert!(back_culled.contains(&lower_right) && !back_culled.contains(&upper_left));
+        assert!(front_culled.contains(&upper_left) && !front_culled.contains(&lower_right));
+    }
+
+    #[test]
+    fn test_wrap_mode() {
+        // A row of four texels, the red channel telling which
+        let texture = Png {
+            width: 4,
+            height: 1,
+            bit_depth: BitDepth::Bits8,
+            color_type: ColorType::RGB,
+            bytes_per_pixel: 3,
+            data: vec![0, 0, 0, 1, 0, 0, 2, 0, 0, 3, 0, 0],
+        };
+        let texel =
+            |u: f32, v: f32, wrap_mode| color_from_texture(&texture, Vec2f::new(u, v), wrap_mode).r;
+
+        assert_eq!(texel(0.5, 0.5, WrapMode::Repeat), 2);
+        assert_eq!(texel(1.5, 0.5, WrapMode::Repeat), 2);
+        assert_eq!(texel(-0.25, 0.5, WrapMode::Repeat), 3);
+        assert_eq!(texel(1.0, 1.0, WrapMode::Repeat), 0);
+
+        assert_eq!(texel(0.5, 0.5, WrapMode::Clamp), 2);
+        assert_eq!(texel(1.5, 0.5, WrapMode::Clamp), 3);
+        assert_eq!(texel(-0.25, -3.0, WrapMode::Clamp), 0);
+        assert_eq!(texel(1.0, 1.0, WrapMode::Clamp), 3);
     }
 }
@@ -1,3 +1,4 @@
 pub mod camera;
 pub mod color;
 pub mod cpu;
+pub mod light;
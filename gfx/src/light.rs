@@ -0,0 +1,62 @@
+use crate::color::Color;
+use math::Vec3f;
+
+/// A directional light, e.g. the sun, shining toward `direction` everywhere, with the constant
+/// `ambient` term lighting also the surfaces facing away from it
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Light {
+    pub direction: Vec3f,
+    pub color: Color,
+    pub ambient: f32,
+}
+
+impl Light {
+    /// Light a surface of `color` with the normal `normal` using Lambertian diffuse reflection,
+    /// i.e. the reflected light is proportional to `max(0, n · -direction)`, plus ambient.
+    /// Neither `normal` nor `direction` need to be of unit length.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use gfx::color::Color;
+    /// use gfx::light::Light;
+    /// use math::Vec3f;
+    ///
+    /// let light = Light {
+    ///     direction: Vec3f::new(0.0, 0.0, -2.0),
+    ///     color: Color::rgb(255, 255, 255),
+    ///     ambient: 0.2,
+    /// };
+    /// let surface = Color::rgb(200, 100, 50);
+    ///
+    /// let facing = light.shade(surface, Vec3f::new(0.0, 0.0, 1.0));
+    /// let tilted = light.shade(surface, Vec3f::new(0.0, 1.0, 1.0));
+    /// let away = light.shade(surface, Vec3f::new(0.0, 0.0, -1.0));
+    /// assert_eq!(facing, Color::rgb(240, 120, 60));
+    /// assert!(tilted.r < facing.r && away.r < tilted.r);
+    /// assert_eq!(away, Color::rgb(40, 20, 10));
+    ///
+    /// let red_light = Light {
+    ///     color: Color::rgb(255, 0, 0),
+    ///     ..light
+    /// };
+    /// let red = red_light.shade(surface, Vec3f::new(0.0, 0.0, 1.0));
+    /// assert_eq!(red, Color::rgb(240, 20, 10));
+    /// ```
+    pub fn shade(&self, color: Color, normal: Vec3f) -> Color {
+        let diffuse = match (normal.try_unit(), self.direction.try_unit()) {
+            (Some(n), Some(direction)) => n.dot(-direction).max(0.0),
+            _ => 0.0,
+        };
+        let channel = |c: u8, light_c: u8| {
+            let intensity = self.ambient + diffuse * light_c as f32 / 255.0;
+            (c as f32 * intensity).round().clamp(0.0, 255.0) as u8
+        };
+        Color::rgba(
+            channel(color.r, self.color.r),
+            channel(color.g, self.color.g),
+            channel(color.b, self.color.b),
+            color.a,
+        )
+    }
+}
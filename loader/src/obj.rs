@@ -1,6 +1,8 @@
 use anyhow::{anyhow, Result};
 use math::{Vec2f, Vec3f};
 use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+use std::fs;
 use std::fs::File;
 use std::io;
 use std::io::BufRead;
@@ -33,48 +35,126 @@ impl Obj {
         let mut obj = Obj::default();
 
         for (line_num, maybe_line) in io::BufReader::new(f).lines().enumerate() {
-            let line = maybe_line?;
-            if line.is_empty() {
-                continue;
-            }
+            obj.parse_line(line_num, &maybe_line?)?;
+        }
+        Ok(obj)
+    }
+
+    /// Like `from_file`, but reads the whole file into memory and counts the vertices, uvs,
+    /// normals and faces first, so that the `Vec`s are allocated once instead of growing while
+    /// parsing. Costs holding the whole file in memory while parsing.
+    pub fn from_file_preallocated<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let contents = fs::read_to_string(path)?;
+        let mut obj = Obj::default();
+
+        let (mut n_vertices, mut n_uvs, mut n_normals, mut n_triangles) = (0, 0, 0, 0);
+        for line in contents.lines() {
             let mut elems = line.split_whitespace();
-            let line_type = elems.next().ok_or_else(|| anyhow!("No line type"))?;
-            match line_type {
-                "v" => {
-                    obj.vertices.push(parse_vec3f(elems)?);
-                }
-                "vt" => {
-                    obj.uvs.push(parse_vec2f(elems)?);
-                }
-                "vn" => {
-                    obj.normals.push(parse_vec3f(elems)?);
-                }
-                "f" => {
-                    for triple in parse_face(elems)?.windows(3) {
-                        obj.vertex_index_triples.push((
-                            triple[0].0 - 1,
-                            triple[1].0 - 1,
-                            triple[2].0 - 1,
-                        ));
-                        obj.uv_index_triples.push((
-                            triple[0].1 - 1,
-                            triple[1].1 - 1,
-                            triple[2].1 - 1,
-                        ));
-                        obj.normal_index_triples.push((
-                            triple[0].2 - 1,
-                            triple[1].2 - 1,
-                            triple[2].2 - 1,
-                        ));
-                    }
+            match elems.next() {
+                Some("v") => n_vertices += 1,
+                Some("vt") => n_uvs += 1,
+                Some("vn") => n_normals += 1,
+                Some("f") => n_triangles += elems.count().saturating_sub(2),
+                _ => {}
+            }
+        }
+        obj.vertices.reserve_exact(n_vertices);
+        obj.uvs.reserve_exact(n_uvs);
+        obj.normals.reserve_exact(n_normals);
+        obj.vertex_index_triples.reserve(n_triangles);
+        obj.uv_index_triples.reserve(n_triangles);
+        obj.normal_index_triples.reserve(n_triangles);
+
+        for (line_num, line) in contents.lines().enumerate() {
+            obj.parse_line(line_num, line)?;
+        }
+        Ok(obj)
+    }
+
+    /// Build an interleaved vertex buffer and an index buffer for uploading to the GPU. Each
+    /// unique (vertex, uv, normal) index combination becomes one vertex of 8 floats, laid out
+    /// as position (x, y, z), normal (x, y, z), uv (u, v). Each triangle is three consecutive
+    /// indices into the vertex buffer, counted in vertices.
+    pub fn to_interleaved_f32(&self) -> (Vec<f32>, Vec<u32>) {
+        let mut vertex_buffer = Vec::new();
+        let mut index_buffer = Vec::with_capacity(3 * self.vertex_index_triples.len());
+        let mut vertex_indices: HashMap<(u32, u32, u32), u32> = HashMap::new();
+
+        for i in 0..self.vertex_index_triples.len() {
+            let v = self.vertex_index_triples[i];
+            let t = self.uv_index_triples[i];
+            let n = self.normal_index_triples[i];
+
+            for &key in &[(v.0, t.0, n.0), (v.1, t.1, n.1), (v.2, t.2, n.2)] {
+                let index = *vertex_indices.entry(key).or_insert_with(|| {
+                    let (v, t, n) = key;
+                    vertex_buffer.extend(self.vertices[v as usize].iter());
+                    vertex_buffer.extend(self.normals[n as usize].iter());
+                    vertex_buffer.extend(self.uvs[t as usize].iter());
+                    (vertex_buffer.len() / 8 - 1) as u32
+                });
+                index_buffer.push(index);
+            }
+        }
+
+        (vertex_buffer, index_buffer)
+    }
+
+    /// Collect the undirected edges of the faces as vertex index pairs, each edge once, with the
+    /// smaller index first. Edges are in the order they are first encountered.
+    pub fn unique_edges(&self) -> Vec<(u32, u32)> {
+        let mut seen = HashSet::new();
+        let mut edges = Vec::new();
+
+        for &(a, b, c) in &self.vertex_index_triples {
+            for &(start, end) in &[(a, b), (b, c), (c, a)] {
+                let edge = (start.min(end), start.max(end));
+                if seen.insert(edge) {
+                    edges.push(edge);
                 }
-                "#" => {}
-                _ => {
-                    println!("Skipping line {}: {}", line_num, line);
+            }
+        }
+        edges
+    }
+
+    fn parse_line(&mut self, line_num: usize, line: &str) -> Result<()> {
+        if line.is_empty() {
+            return Ok(());
+        }
+        let mut elems = line.split_whitespace();
+        let line_type = elems.next().ok_or_else(|| anyhow!("No line type"))?;
+        match line_type {
+            "v" => {
+                self.vertices.push(parse_vec3f(elems)?);
+            }
+            "vt" => {
+                self.uvs.push(parse_vec2f(elems)?);
+            }
+            "vn" => {
+                self.normals.push(parse_vec3f(elems)?);
+            }
+            "f" => {
+                for triple in parse_face(elems)?.windows(3) {
+                    self.vertex_index_triples.push((
+                        triple[0].0 - 1,
+                        triple[1].0 - 1,
+                        triple[2].0 - 1,
+                    ));
+                    self.uv_index_triples
+                        .push((triple[0].1 - 1, triple[1].1 - 1, triple[2].1 - 1));
+                    self.normal_index_triples.push((
+                        triple[0].2 - 1,
+                        triple[1].2 - 1,
+                        triple[2].2 - 1,
+                    ));
                 }
             }
+            "#" => {}
+            _ => {
+                println!("Skipping line {}: {}", line_num, line);
+            }
         }
-        Ok(obj)
+        Ok(())
     }
 }
 
@@ -158,3 +238,72 @@ fn parse_face_index_triple<'a, T: Iterator<Item = &'a str>>(
 
     Ok((v, vt, vn))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A unit quad split into two triangles sharing the edge 0-2
+    fn quad() -> Obj {
+        Obj {
+            vertices: vec![
+                Vec3f::new(0.0, 0.0, 0.0),
+                Vec3f::new(1.0, 0.0, 0.0),
+                Vec3f::new(1.0, 1.0, 0.0),
+                Vec3f::new(0.0, 1.0, 0.0),
+            ],
+            uvs: vec![
+                Vec2f::new(0.0, 0.0),
+                Vec2f::new(1.0, 0.0),
+                Vec2f::new(1.0, 1.0),
+                Vec2f::new(0.0, 1.0),
+            ],
+            normals: vec![Vec3f::new(0.0, 0.0, 1.0)],
+            vertex_index_triples: vec![(0, 1, 2), (0, 2, 3)],
+            uv_index_triples: vec![(0, 1, 2), (0, 2, 3)],
+            normal_index_triples: vec![(0, 0, 0), (0, 0, 0)],
+        }
+    }
+
+    #[test]
+    fn test_to_interleaved_f32() {
+        let obj = quad();
+        let (vertex_buffer, index_buffer) = obj.to_interleaved_f32();
+
+        assert_eq!(vertex_buffer.len(), 4 * 8);
+        assert_eq!(index_buffer.len(), 6);
+
+        for (face, indices) in index_buffer.chunks(3).enumerate() {
+            let v = obj.vertex_index_triples[face];
+            let t = obj.uv_index_triples[face];
+            for (&index, (v, t)) in indices.iter().zip(&[(v.0, t.0), (v.1, t.1), (v.2, t.2)]) {
+                let vertex = &vertex_buffer[8 * index as usize..8 * (index as usize + 1)];
+                let position = obj.vertices[*v as usize];
+                let uv = obj.uvs[*t as usize];
+                assert_eq!(vertex[..3], [position.x(), position.y(), position.z()]);
+                assert_eq!(vertex[3..6], [0.0, 0.0, 1.0]);
+                assert_eq!(vertex[6..], [uv.x(), uv.y()]);
+            }
+        }
+    }
+
+    #[test]
+    fn test_unique_edges() {
+        let edges = quad().unique_edges();
+        assert_eq!(edges, vec![(0, 1), (1, 2), (0, 2), (2, 3), (0, 3)]);
+    }
+
+    #[test]
+    fn test_from_file_preallocated() {
+        let path = "../bin/obj/assets/head.obj";
+        let streamed = Obj::from_file(path).unwrap();
+        let preallocated = Obj::from_file_preallocated(path).unwrap();
+
+        assert!(!streamed.vertex_index_triples.is_empty());
+        assert_eq!(
+            preallocated.vertices.capacity(),
+            preallocated.vertices.len()
+        );
+        assert_eq!(streamed, preallocated);
+    }
+}
@@ -21,7 +21,32 @@ pub struct Png {
     pub data: Vec<u8>,
 }
 
+/// The image properties stored in the IHDR chunk of a PNG
+#[derive(PartialEq, Debug)]
+pub struct PngInfo {
+    pub width: u32,
+    pub height: u32,
+    pub bit_depth: BitDepth,
+    pub color_type: ColorType,
+    pub bytes_per_pixel: u32,
+}
+
 impl Png {
+    /// Read just the PNG header and the IHDR chunk, without decoding the image.
+    pub fn read_header<R: Read>(reader: R) -> Result<PngInfo> {
+        let mut digest_reader = DigestReader::new(reader, Crc32::new());
+        read_png_header(&mut digest_reader)?;
+        let ihdr = read_ihdr(&mut digest_reader)?;
+
+        Ok(PngInfo {
+            width: ihdr.width,
+            height: ihdr.height,
+            bit_depth: ihdr.bit_depth,
+            color_type: ihdr.color_type,
+            bytes_per_pixel: ihdr.bytes_per_pixel,
+        })
+    }
+
     pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Png> {
         let f = File::open(path)?;
         Png::from_reader(f)
@@ -57,6 +82,112 @@ impl Png {
             data: image,
         })
     }
+
+    /// Expand an 8-bit grayscale or RGB image into an 8-bit RGB image, e.g. for uploading to an
+    /// RGB24 texture.
+    pub fn to_rgb8(&self) -> Result<Png> {
+        let data = match (&self.bit_depth, &self.color_type) {
+            (BitDepth::Bits8, ColorType::Grayscale) => {
+                self.data.iter().flat_map(|&v| [v, v, v]).collect()
+            }
+            (BitDepth::Bits8, ColorType::RGB) => self.data.clone(),
+            (bit_depth, color_type) => {
+                bail!("Can't convert to RGB8: {:?}, {:?}", bit_depth, color_type)
+            }
+        };
+
+        Ok(Png {
+            width: self.width,
+            height: self.height,
+            bit_depth: BitDepth::Bits8,
+            color_type: ColorType::RGB,
+            bytes_per_pixel: 3,
+            data,
+        })
+    }
+
+    /// Expand an 8-bit image of any color type but Palette into an 8-bit RGBA image, with opaque
+    /// alpha for color types that have none.
+    pub fn to_rgba8(&self) -> Result<Png> {
+        let data = match (&self.bit_depth, &self.color_type) {
+            (BitDepth::Bits8, ColorType::Grayscale) => {
+                self.data.iter().flat_map(|&v| [v, v, v, 0xff]).collect()
+            }
+            (BitDepth::Bits8, ColorType::GrayscaleAlpha) => self
+                .data
+                .chunks_exact(2)
+                .flat_map(|p| [p[0], p[0], p[0], p[1]])
+                .collect(),
+            (BitDepth::Bits8, ColorType::RGB) => self
+                .data
+                .chunks_exact(3)
+                .flat_map(|p| [p[0], p[1], p[2], 0xff])
+                .collect(),
+            (BitDepth::Bits8, ColorType::RGBA) => self.data.clone(),
+            (bit_depth, color_type) => {
+                bail!("Can't convert to RGBA8: {:?}, {:?}", bit_depth, color_type)
+            }
+        };
+
+        Ok(Png {
+            width: self.width,
+            height: self.height,
+            bit_depth: BitDepth::Bits8,
+            color_type: ColorType::RGBA,
+            bytes_per_pixel: 4,
+            data,
+        })
+    }
+
+    /// Do the images have the same dimensions and pixels, regardless of their color types.
+    /// Images that can't be converted with `to_rgba8` are never equal.
+    pub fn pixels_equal(&self, other: &Png) -> bool {
+        if self.width != other.width || self.height != other.height {
+            return false;
+        }
+        match (self.to_rgba8(), other.to_rgba8()) {
+            (Ok(a), Ok(b)) => a.data == b.data,
+            _ => false,
+        }
+    }
+
+    /// The samples of a 16-bit image, decoded from the big-endian bytes in `data`. Returns `None`
+    /// for images of other bit depths.
+    pub fn samples_u16(&self) -> Option<Vec<u16>> {
+        if self.bit_depth != BitDepth::Bits16 {
+            return None;
+        }
+        Some(
+            self.data
+                .chunks_exact(2)
+                .map(|b| u16::from_be_bytes([b[0], b[1]]))
+                .collect(),
+        )
+    }
+
+    /// Flip the image upside down, i.e. reverse the order of the scanlines.
+    pub fn flip_vertical(&mut self) {
+        let scanline_len = (self.width * self.bytes_per_pixel) as usize;
+        let height = self.height as usize;
+        for y in 0..height / 2 {
+            let (top, bottom) = self.data.split_at_mut((height - 1 - y) * scanline_len);
+            top[y * scanline_len..(y + 1) * scanline_len]
+                .swap_with_slice(&mut bottom[..scanline_len]);
+        }
+    }
+
+    /// Mirror the image, i.e. reverse the order of the pixels within each scanline.
+    pub fn flip_horizontal(&mut self) {
+        let bpp = self.bytes_per_pixel as usize;
+        let scanline_len = self.width as usize * bpp;
+        for scanline in self.data.chunks_mut(scanline_len) {
+            scanline.reverse();
+            // Reversing the whole scanline also reversed the bytes within each pixel
+            for pixel in scanline.chunks_mut(bpp) {
+                pixel.reverse();
+            }
+        }
+    }
 }
 //
 // PNG file header
@@ -92,6 +223,9 @@ enum ChunkType {
     PLTE,
     IDAT,
     IEND,
+    // Unknown chunks. Per the spec, the case of the first letter tells whether a decoder may
+    // safely ignore the chunk (lowercase) or not (uppercase).
+    Critical(String),
     Ancillary(String),
 }
 
@@ -107,6 +241,7 @@ fn read_chunk_type<R: Read>(reader: &mut R) -> Result<ChunkType> {
         "PLTE" => PLTE,
         "IDAT" => IDAT,
         "IEND" => IEND,
+        _ if b[0].is_ascii_uppercase() => Critical(chunk_type_str.to_string()),
         _ => Ancillary(chunk_type_str.to_string()),
     };
 
@@ -394,7 +529,7 @@ fn apply_filters(ihdr: &IHDR, decompressed_data: &mut Vec<u8>, image: &mut Vec<u
                 let image_idx = scanline_len * scanline_idx;
                 image[image_idx..image_idx + scanline_len]
                     .as_mut()
-                    .write_all(&scanline[1..])?;
+                    .write_all(scanline)?;
             }
         }
     }
@@ -478,6 +613,7 @@ fn process_chunk<R: Read>(
         }
         ChunkType::PLTE => bail!("Can't handle PNGs with palette yet!"),
         ChunkType::IHDR => bail!("Encountered a second IHDR chunk"),
+        ChunkType::Critical(chunk_type) => bail!("Unknown critical chunk {}", chunk_type),
         _ => {
             println!("Skipping {:?}, {} bytes", chunk_type, chunk_length);
             skip_bytes(&mut reader, chunk_length)?;
@@ -518,3 +654,183 @@ fn skip_bytes<R: Read>(reader: &mut R, n: u32) -> Result<()> {
     reader.read_exact(&mut v)?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use digest::Digest;
+
+    // 2x2 8-bit grayscale image with pixels [0x00, 0x40, 0x80, 0xff]
+    const GRAYSCALE_PNG: [u8; 71] = [
+        0x89, 0x50, 0x4e, 0x47, 0x0d, 0x0a, 0x1a, 0x0a, 0x00, 0x00, 0x00, 0x0d, 0x49, 0x48, 0x44,
+        0x52, 0x00, 0x00, 0x00, 0x02, 0x00, 0x00, 0x00, 0x02, 0x08, 0x00, 0x00, 0x00, 0x00, 0x57,
+        0xdd, 0x52, 0xf8, 0x00, 0x00, 0x00, 0x0e, 0x49, 0x44, 0x41, 0x54, 0x78, 0x9c, 0x63, 0x60,
+        0x70, 0x60, 0x68, 0xf8, 0x0f, 0x00, 0x03, 0x05, 0x01, 0xc0, 0x4e, 0x33, 0x5b, 0xe9, 0x00,
+        0x00, 0x00, 0x00, 0x49, 0x45, 0x4e, 0x44, 0xae, 0x42, 0x60, 0x82,
+    ];
+
+    #[test]
+    fn test_grayscale_to_rgb8() {
+        let png = Png::from_reader(&GRAYSCALE_PNG[..]).unwrap();
+        assert_eq!(png.color_type, ColorType::Grayscale);
+        assert_eq!(png.bytes_per_pixel, 1);
+        assert_eq!(png.data, vec![0x00, 0x40, 0x80, 0xff]);
+
+        let rgb = png.to_rgb8().unwrap();
+        assert_eq!(rgb.color_type, ColorType::RGB);
+        assert_eq!(rgb.bytes_per_pixel, 3);
+        assert_eq!(rgb.data.len(), 3 * 2 * 2);
+        assert_eq!(
+            rgb.data,
+            vec![0x00, 0x00, 0x00, 0x40, 0x40, 0x40, 0x80, 0x80, 0x80, 0xff, 0xff, 0xff]
+        );
+    }
+
+    #[test]
+    fn test_grayscale_alpha_to_rgba8() {
+        let png = Png {
+            width: 2,
+            height: 1,
+            bit_depth: BitDepth::Bits8,
+            color_type: ColorType::GrayscaleAlpha,
+            bytes_per_pixel: 2,
+            data: vec![0x40, 0xff, 0x80, 0x00],
+        };
+        let rgba = png.to_rgba8().unwrap();
+        assert_eq!(rgba.color_type, ColorType::RGBA);
+        assert_eq!(rgba.bytes_per_pixel, 4);
+        assert_eq!(
+            rgba.data,
+            vec![0x40, 0x40, 0x40, 0xff, 0x80, 0x80, 0x80, 0x00]
+        );
+        assert!(png.to_rgb8().is_err());
+    }
+
+    fn chunk(chunk_type: &[u8; 4], data: &[u8]) -> Vec<u8> {
+        let mut crc = Crc32::new();
+        for b in chunk_type.iter().chain(data) {
+            crc.update(*b);
+        }
+        let mut bytes = (data.len() as u32).to_be_bytes().to_vec();
+        bytes.extend_from_slice(chunk_type);
+        bytes.extend_from_slice(data);
+        bytes.extend_from_slice(&crc.digest().to_be_bytes());
+        bytes
+    }
+
+    // 2x1 16-bit grayscale image with samples 0x1234 and 0xfedc
+    const GRAYSCALE16_PNG: [u8; 70] = [
+        0x89, 0x50, 0x4e, 0x47, 0x0d, 0x0a, 0x1a, 0x0a, 0x00, 0x00, 0x00, 0x0d, 0x49, 0x48, 0x44,
+        0x52, 0x00, 0x00, 0x00, 0x02, 0x00, 0x00, 0x00, 0x01, 0x10, 0x00, 0x00, 0x00, 0x00, 0x81,
+        0xd9, 0xfc, 0x15, 0x00, 0x00, 0x00, 0x0d, 0x49, 0x44, 0x41, 0x54, 0x78, 0x9c, 0x63, 0x10,
+        0x32, 0xf9, 0x77, 0x07, 0x00, 0x03, 0xc1, 0x02, 0x21, 0x0d, 0xe3, 0x47, 0x32, 0x00, 0x00,
+        0x00, 0x00, 0x49, 0x45, 0x4e, 0x44, 0xae, 0x42, 0x60, 0x82,
+    ];
+
+    #[test]
+    fn test_samples_u16() {
+        let png = Png::from_reader(&GRAYSCALE16_PNG[..]).unwrap();
+        assert_eq!(png.bit_depth, BitDepth::Bits16);
+        assert_eq!(png.bytes_per_pixel, 2);
+        assert_eq!(png.samples_u16(), Some(vec![0x1234, 0xfedc]));
+
+        let png = Png::from_reader(&GRAYSCALE_PNG[..]).unwrap();
+        assert_eq!(png.samples_u16(), None);
+    }
+
+    #[test]
+    fn test_read_header() {
+        let ihdr_end = 8 + 4 + 4 + 13 + 4;
+        // Only the header and IHDR are needed
+        let info = Png::read_header(&GRAYSCALE16_PNG[..ihdr_end]).unwrap();
+        assert_eq!(
+            info,
+            PngInfo {
+                width: 2,
+                height: 1,
+                bit_depth: BitDepth::Bits16,
+                color_type: ColorType::Grayscale,
+                bytes_per_pixel: 2,
+            }
+        );
+
+        let f = File::open("../bin/png/assets/large.png").unwrap();
+        let info = Png::read_header(f).unwrap();
+        assert_eq!((info.width, info.height), (1653, 2316));
+        assert_eq!(info.color_type, ColorType::RGBA);
+
+        assert!(Png::read_header(&GRAYSCALE16_PNG[..ihdr_end - 1]).is_err());
+    }
+
+    // Insert `chunk` right after the IHDR chunk of `GRAYSCALE_PNG`
+    fn grayscale_png_with_chunk(chunk: &[u8]) -> Vec<u8> {
+        let ihdr_end = 8 + 4 + 4 + 13 + 4;
+        let mut bytes = GRAYSCALE_PNG[..ihdr_end].to_vec();
+        bytes.extend_from_slice(chunk);
+        bytes.extend_from_slice(&GRAYSCALE_PNG[ihdr_end..]);
+        bytes
+    }
+
+    #[test]
+    fn test_unknown_chunks() {
+        let bytes = grayscale_png_with_chunk(&chunk(b"tEXt", b"Comment\0Hello"));
+        let png = Png::from_reader(&bytes[..]).unwrap();
+        assert_eq!(png.data, vec![0x00, 0x40, 0x80, 0xff]);
+
+        let bytes = grayscale_png_with_chunk(&chunk(b"AbCD", b"data"));
+        assert!(Png::from_reader(&bytes[..]).is_err());
+    }
+
+    fn rgb_2x2() -> Png {
+        Png {
+            width: 2,
+            height: 2,
+            bit_depth: BitDepth::Bits8,
+            color_type: ColorType::RGB,
+            bytes_per_pixel: 3,
+            data: vec![1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12],
+        }
+    }
+
+    #[test]
+    fn test_pixels_equal() {
+        let rgb = rgb_2x2();
+        let rgba = Png {
+            color_type: ColorType::RGBA,
+            bytes_per_pixel: 4,
+            data: vec![
+                1, 2, 3, 0xff, 4, 5, 6, 0xff, 7, 8, 9, 0xff, 10, 11, 12, 0xff,
+            ],
+            ..rgb_2x2()
+        };
+        assert_ne!(rgb, rgba);
+        assert!(rgb.pixels_equal(&rgba));
+        assert!(rgba.pixels_equal(&rgb));
+
+        let mut translucent = rgba.to_rgba8().unwrap();
+        translucent.data[3] = 0x80;
+        assert!(!rgb.pixels_equal(&translucent));
+
+        let mut flipped = rgb_2x2();
+        flipped.flip_vertical();
+        assert!(!rgb.pixels_equal(&flipped));
+    }
+
+    #[test]
+    fn test_flip_vertical() {
+        let mut png = rgb_2x2();
+        png.flip_vertical();
+        assert_eq!(png.data, vec![7, 8, 9, 10, 11, 12, 1, 2, 3, 4, 5, 6]);
+        png.flip_vertical();
+        assert_eq!(png, rgb_2x2());
+    }
+
+    #[test]
+    fn test_flip_horizontal() {
+        let mut png = rgb_2x2();
+        png.flip_horizontal();
+        assert_eq!(png.data, vec![4, 5, 6, 1, 2, 3, 10, 11, 12, 7, 8, 9]);
+        png.flip_horizontal();
+        assert_eq!(png, rgb_2x2());
+    }
+}
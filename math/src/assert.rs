@@ -1,10 +1,77 @@
+use crate::matrix::Matrix;
+use crate::vector::Vector;
+
+/// The largest absolute difference between corresponding elements of `self` and `other`, used
+/// by `assert_eq_eps!` to compare scalars, vectors and matrices alike.
+///
+/// # Examples
+///
+/// ```rust
+/// use math::assert::MaxAbsDiff;
+/// use math::assert_eq_eps;
+/// use math::{Matrix2f, Vec3f};
+///
+/// assert_eq!(1.0f32.max_abs_diff(&0.5), 0.5);
+/// assert_eq!(Vec3f::new(1.0, 2.0, 3.0).max_abs_diff(&Vec3f::new(1.0, 1.0, 4.5)), 1.5);
+///
+/// let m = Matrix2f::new(1.0, 0.0, 0.0, 1.0);
+/// assert_eq_eps!(m, Matrix2f::new(1.0, 1e-9, 0.0, 1.0), 1e-6);
+/// ```
+///
+/// ```rust,should_panic
+/// use math::assert_eq_eps;
+/// use math::Matrix2f;
+///
+/// let m = Matrix2f::new(1.0, 0.0, 0.0, 1.0);
+/// assert_eq_eps!(m, Matrix2f::new(1.0, 1e-3, 0.0, 1.0), 1e-6);
+/// ```
+pub trait MaxAbsDiff {
+    fn max_abs_diff(&self, other: &Self) -> f32;
+}
+
+impl MaxAbsDiff for f32 {
+    fn max_abs_diff(&self, other: &Self) -> f32 {
+        (self - other).abs()
+    }
+}
+
+impl MaxAbsDiff for f64 {
+    fn max_abs_diff(&self, other: &Self) -> f32 {
+        (self - other).abs() as f32
+    }
+}
+
+impl MaxAbsDiff for i32 {
+    fn max_abs_diff(&self, other: &Self) -> f32 {
+        (self - other).abs() as f32
+    }
+}
+
+impl<const N: usize> MaxAbsDiff for Vector<f32, N> {
+    fn max_abs_diff(&self, other: &Self) -> f32 {
+        self.iter()
+            .zip(other.iter())
+            .map(|(a, b)| a.max_abs_diff(&b))
+            .fold(0.0, f32::max)
+    }
+}
+
+impl<const N: usize> MaxAbsDiff for Matrix<f32, N> {
+    fn max_abs_diff(&self, other: &Self) -> f32 {
+        self.columns()
+            .zip(other.columns())
+            .map(|(a, b)| a.max_abs_diff(b))
+            .fold(0.0, f32::max)
+    }
+}
+
 // A variation on assert_eq from the standard library
 #[macro_export]
 macro_rules! assert_eq_eps {
     ($left:expr, $right:expr, $epsilon:expr $(,)?) => ({
         match (&$left, &$right, &$epsilon) {
             (left_val, right_val, epsilon) => {
-                if !((*left_val - *right_val).abs() < *epsilon) {
+                if !($crate::assert::MaxAbsDiff::max_abs_diff(left_val, right_val) < *epsilon) {
                     panic!(r#"assertion failed: `(left == right)`
   left: `{:?}`,
  right: `{:?}`"#, &*left_val, &*right_val)
@@ -15,7 +82,7 @@ macro_rules! assert_eq_eps {
     ($left:expr, $right:expr, $epsilon:expr, $($arg:tt)+) => ({
         match (&($left), &($right), &($epsilon)) {
             (left_val, right_val, epsilon) => {
-                if !((*left_val - *right_val).abs() < *epsilon) {
+                if !($crate::assert::MaxAbsDiff::max_abs_diff(left_val, right_val) < *epsilon) {
                     panic!(r#"assertion failed: `(left == right)`
   left: `{:?}`,
  right: `{:?}`: {}"#, &*left_val, &*right_val,
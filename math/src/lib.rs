@@ -1,5 +1,6 @@
 pub mod assert;
 pub mod matrix;
+pub mod quaternion;
 pub mod vector;
 
 pub use matrix::Matrix;
@@ -16,6 +17,8 @@ pub use matrix::Matrix4;
 pub use matrix::Matrix4f;
 pub use matrix::Matrix4i;
 
+pub use quaternion::Quaternion;
+
 pub use vector::Vector;
 
 pub use vector::Vec2;
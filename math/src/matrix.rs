@@ -1,6 +1,8 @@
 use crate::vector::{Vec3, Vec3f, VecElem, Vector};
 use crate::{Vec2, Vec4};
 pub use num::{Float, One, Zero};
+use std::cmp::{self, Ordering};
+use std::fmt;
 use std::iter::FromIterator;
 use std::ops::{Add, Mul, Sub};
 use std::slice::Iter;
@@ -33,6 +35,25 @@ impl<T: VecElem, const N: usize> Matrix<T, N> {
         self
     }
 
+    /// Returns the matrix with `diagonal` on the diagonal and zeros elsewhere.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use math::matrix::*;
+    /// use math::vector::*;
+    ///
+    /// assert_eq!(Matrix3f::diagonal(Vec3f::new(1.0, 1.0, 1.0)), Matrix3f::one());
+    /// assert_eq!(Matrix2i::diagonal(Vec2i::new(2, 3)), Matrix2i::new(2, 0, 0, 3));
+    /// ```
+    pub fn diagonal(diagonal: Vector<T, N>) -> Self {
+        let mut m = Self::zero();
+        for i in 0..N {
+            m.set(i, i, diagonal[i]);
+        }
+        m
+    }
+
     pub fn row(&self, row: usize) -> Vector<T, N> {
         let mut v: Vector<T, N> = Vector::zero();
         for i in 0..N {
@@ -53,10 +74,60 @@ impl<T: VecElem, const N: usize> Matrix<T, N> {
         self.into_iter()
     }
 
+    /// Iterate over all elements of the matrix in column major order.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use math::matrix::*;
+    ///
+    /// let m = Matrix2f::new(1.0, 2.0, 3.0, 4.0);
+    /// let elements: Vec<f32> = m.elements().collect();
+    ///
+    /// assert_eq!(elements, vec![1.0, 3.0, 2.0, 4.0]);
+    /// ```
+    pub fn elements(&self) -> impl Iterator<Item = T> + '_ {
+        self.columns().flat_map(|col| col.iter())
+    }
+
+    /// Returns the Frobenius norm, i.e. the square root of the sum of squared elements.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use math::matrix::*;
+    ///
+    /// assert_eq!(Matrix3f::one().frobenius_norm(), 3.0f32.sqrt());
+    /// assert_eq!(Matrix4f::one().frobenius_norm(), 2.0);
+    /// // sqrt(1 + 4 + 4 + 16) = 5
+    /// assert_eq!(Matrix2i::new(1, -2, 2, 4).frobenius_norm(), 5.0);
+    /// ```
+    pub fn frobenius_norm(&self) -> f32 {
+        self.elements()
+            .map(|e| e.as_f32() * e.as_f32())
+            .sum::<f32>()
+            .sqrt()
+    }
+
     //
     // Basic matrix operations
     //
 
+    /// Returns the sum of the diagonal elements.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use math::matrix::*;
+    ///
+    /// assert_eq!(Matrix3f::one().trace(), 3.0);
+    /// assert_eq!(Matrix2i::new(1, 2, 3, -4).trace(), -3);
+    /// assert_eq!(Matrix3f::new(1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0, 9.0).trace(), 15.0);
+    /// ```
+    pub fn trace(&self) -> T {
+        (0..N).fold(T::zero(), |acc, i| acc + self.get(i, i))
+    }
+
     /// # Examples
     ///
     /// ```rust
@@ -99,6 +170,50 @@ impl<T: VecElem> Matrix2<T> {
     pub fn from_rows(x: Vec2<T>, y: Vec2<T>) -> Self {
         Self::new(x[0], x[1], y[0], y[1])
     }
+
+    /// # Examples
+    ///
+    /// ```rust
+    /// use math::matrix::*;
+    ///
+    /// assert_eq!(Matrix2f::one().determinant(), 1.0);
+    /// assert_eq!(Matrix2i::new(1, 2, 2, 4).determinant(), 0);
+    /// assert_eq!(Matrix2i::new(3, 1, 4, 2).determinant(), 2);
+    /// ```
+    pub fn determinant(&self) -> T {
+        self.get(0, 0) * self.get(1, 1) - self.get(0, 1) * self.get(1, 0)
+    }
+}
+
+impl Matrix2f {
+    /// # Examples
+    ///
+    /// ```rust
+    /// use math::matrix::*;
+    ///
+    /// let id = Matrix2f::one();
+    /// let singular = Matrix2f::new(1.0, 2.0, 2.0, 4.0);
+    /// let rotation = Matrix2f::new(0.0, -1.0, 1.0, 0.0);
+    ///
+    /// assert_eq!(id.inverse().unwrap(), id);
+    /// assert_eq!(singular.inverse(), None);
+    /// assert_eq!(rotation.inverse().unwrap(), rotation.transpose());
+    /// assert_eq!(Matrix2f::new(4.0, 7.0, 2.0, 6.0).inverse().unwrap(),
+    ///            Matrix2f::new(0.6, -0.7, -0.2, 0.4));
+    /// ```
+    pub fn inverse(&self) -> Option<Self> {
+        let det = self.determinant();
+        if det == 0.0 {
+            return None;
+        }
+        let inv_det = 1.0 / det;
+        Some(Self::new(
+            self.get(1, 1) * inv_det,
+            -self.get(0, 1) * inv_det,
+            -self.get(1, 0) * inv_det,
+            self.get(0, 0) * inv_det,
+        ))
+    }
 }
 
 impl<T: VecElem> Matrix3<T> {
@@ -205,6 +320,177 @@ impl<T: VecElem> From<Matrix3<T>> for Matrix4<T> {
     }
 }
 
+impl<const N: usize> Matrix<f32, N> {
+    /// Whether each element of `self` is less than `eps` away from the corresponding one in
+    /// `other`, the same comparison `assert_eq_eps!` makes.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use math::matrix::*;
+    /// use std::f32::consts::{FRAC_PI_2, PI};
+    ///
+    /// let quarter_turn = Matrix3f::new(0.0, -1.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 1.0);
+    /// assert!(Matrix3f::rotation_z(FRAC_PI_2).approx_eq(quarter_turn, 1e-6));
+    /// assert!(!Matrix3f::rotation_z(-FRAC_PI_2).approx_eq(quarter_turn, 1e-6));
+    /// assert!(Matrix3f::rotation_z(2.0 * PI).approx_eq(Matrix3f::one(), 1e-6));
+    /// ```
+    pub fn approx_eq(&self, other: Self, eps: f32) -> bool {
+        self.columns()
+            .zip(other.columns())
+            .all(|(a, b)| a.approx_eq(*b, eps))
+    }
+
+    /// LU decomposition with partial pivoting. Returns `(l, u, perm)` where `l` is lower
+    /// triangular with ones on the diagonal, `u` is upper triangular and row `i` of `l * u` is
+    /// row `perm[i]` of the matrix. Returns `None` if the matrix is singular.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use math::assert_eq_eps;
+    /// use math::matrix::*;
+    ///
+    /// let m = Matrix3f::new(1.0, 2.0, 3.0, 2.0, 3.0, 4.0, -3.0, 4.0, 5.0);
+    /// let (l, u, perm) = m.lu_decompose().unwrap();
+    /// assert_eq!(perm, [2, 1, 0]);
+    /// for i in 0..3 {
+    ///     for j in 0..3 {
+    ///         assert_eq!(l.get(i, j) == 0.0, j > i);
+    ///         assert_eq!(u.get(i, j) == 0.0, j < i);
+    ///     }
+    ///     assert_eq!(l.get(i, i), 1.0);
+    /// }
+    /// assert_eq_eps!(l * u, Matrix3f::from_rows(m.row(2), m.row(1), m.row(0)), 1e-6);
+    ///
+    /// assert_eq!(Matrix3f::new(1.0, 2.0, 3.0, 2.0, 4.0, 6.0, 0.0, 1.0, 1.0).lu_decompose(), None);
+    /// ```
+    pub fn lu_decompose(&self) -> Option<(Self, Self, [usize; N])> {
+        let mut l = Self::one();
+        let mut u = *self;
+        let mut perm: [usize; N] = std::array::from_fn(|i| i);
+
+        for k in 0..N {
+            let pivot_row = (k..N)
+                .max_by(|&a, &b| {
+                    u.get(a, k)
+                        .abs()
+                        .partial_cmp(&u.get(b, k).abs())
+                        .unwrap_or(Ordering::Equal)
+                })
+                .unwrap_or(k);
+            if u.get(pivot_row, k) == 0.0 {
+                return None;
+            }
+            if pivot_row != k {
+                perm.swap(k, pivot_row);
+                for col in 0..N {
+                    let tmp = u.get(k, col);
+                    u.set(k, col, u.get(pivot_row, col));
+                    u.set(pivot_row, col, tmp);
+                }
+                // Only the multipliers found so far, left of the diagonal, move with the rows
+                for col in 0..k {
+                    let tmp = l.get(k, col);
+                    l.set(k, col, l.get(pivot_row, col));
+                    l.set(pivot_row, col, tmp);
+                }
+            }
+
+            for row in k + 1..N {
+                let factor = u.get(row, k) / u.get(k, k);
+                l.set(row, k, factor);
+                u.set(row, k, 0.0);
+                for col in k + 1..N {
+                    u.set(row, col, u.get(row, col) - factor * u.get(k, col));
+                }
+            }
+        }
+        Some((l, u, perm))
+    }
+
+    /// Solve `x` from `self * x = b` by forward and back substitution with the LU
+    /// decomposition. Returns `None` if the matrix is singular.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use math::assert_eq_eps;
+    /// use math::matrix::*;
+    /// use math::vector::*;
+    ///
+    /// // 2x + y - z = 8, -3x - y + 2z = -11, -2x + y + 2z = -3
+    /// let m = Matrix3f::new(2.0, 1.0, -1.0, -3.0, -1.0, 2.0, -2.0, 1.0, 2.0);
+    /// let b = Vec3f::new(8.0, -11.0, -3.0);
+    /// let x = m.solve(b).unwrap();
+    /// assert_eq_eps!(x, Vec3f::new(2.0, 3.0, -1.0), 1e-5);
+    /// assert_eq_eps!(m * x - b, Vec3f::zero(), 1e-5);
+    ///
+    /// assert_eq!(Matrix2f::new(1.0, 2.0, 2.0, 4.0).solve(Vec2f::new(1.0, 1.0)), None);
+    /// ```
+    pub fn solve(&self, b: Vector<f32, N>) -> Option<Vector<f32, N>> {
+        let (l, u, perm) = self.lu_decompose()?;
+
+        // l * y = b permuted
+        let mut y = Vector::<f32, N>::zero();
+        for row in 0..N {
+            let sum: f32 = (0..row).map(|col| l.get(row, col) * y[col]).sum();
+            y[row] = b[perm[row]] - sum;
+        }
+
+        // u * x = y
+        let mut x = Vector::<f32, N>::zero();
+        for row in (0..N).rev() {
+            let sum: f32 = (row + 1..N).map(|col| u.get(row, col) * x[col]).sum();
+            x[row] = (y[row] - sum) / u.get(row, row);
+        }
+        Some(x)
+    }
+
+    /// The determinant computed from the LU decomposition, for any size of matrix.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use math::assert_eq_eps;
+    /// use math::matrix::*;
+    ///
+    /// for m in &[
+    ///     Matrix3f::zero(),
+    ///     Matrix3f::one(),
+    ///     Matrix3f::new(1.0, 2.0, 3.0, 2.0, 3.0, 4.0, -3.0, 4.0, 5.0),
+    ///     Matrix3f::new(0.0, 1.0, 0.0, -1.0, 0.0, 0.0, 0.0, 0.0, 1.0),
+    ///     Matrix3f::rotation_x(0.3) * 2.0,
+    /// ] {
+    ///     assert_eq_eps!(m.lu_determinant(), m.determinant(), 1e-5);
+    /// }
+    /// assert_eq!(Matrix2f::new(3.0, 1.0, 4.0, 2.0).lu_determinant(), 2.0);
+    /// ```
+    pub fn lu_determinant(&self) -> f32 {
+        let (_, u, perm) = match self.lu_decompose() {
+            Some(lu) => lu,
+            None => return 0.0,
+        };
+
+        // Each cycle of length n in the permutation takes n - 1 swaps
+        let mut visited = [false; N];
+        let mut n_swaps = 0;
+        for start in 0..N {
+            let mut i = start;
+            let mut cycle_length = 0;
+            while !visited[i] {
+                visited[i] = true;
+                i = perm[i];
+                cycle_length += 1;
+            }
+            n_swaps += cmp::max(cycle_length, 1) - 1;
+        }
+
+        let sign = if n_swaps % 2 == 0 { 1.0 } else { -1.0 };
+        (0..N).fold(sign, |acc, i| acc * u.get(i, i))
+    }
+}
+
 impl<T: Float + VecElem + Mul<Matrix3<T>, Output = Matrix3<T>>> Matrix3<T> {
     /// Given vector `a`, return a matrix that, when multiplied with vector `v` returns the same
     /// result as `a.cross(v)`.
@@ -263,30 +549,30 @@ impl<T: Float + VecElem + Mul<Matrix3<T>, Output = Matrix3<T>>> Matrix3<T> {
     /// let k = Vec3f::new(0.0, 0.0, 1.0);
     ///
     /// let rot: Matrix3f = Matrix3f::rotation_z(FRAC_PI_2);
-    /// assert_eq_eps!(rot * i, j, 0.00000001);
+    /// assert_eq_eps!(rot * i, j, 0.000001);
     /// let rot: Matrix3f = Matrix3f::rotation_z(-FRAC_PI_2);
-    /// assert_eq_eps!(rot * i, -j, 0.00000001);
+    /// assert_eq_eps!(rot * i, -j, 0.000001);
     /// let rot: Matrix3f = Matrix3f::rotation_z(PI);
-    /// assert_eq_eps!(rot * i, -i, 0.00000001);
+    /// assert_eq_eps!(rot * i, -i, 0.000001);
     /// let rot: Matrix3f = Matrix3f::rotation_z(FRAC_PI_2);
-    /// assert_eq_eps!(rot * j, -i, 0.00000001);
+    /// assert_eq_eps!(rot * j, -i, 0.000001);
     /// let rot: Matrix3f = Matrix3f::rotation_z(-FRAC_PI_2);
-    /// assert_eq_eps!(rot * j, i, 0.00000001);
+    /// assert_eq_eps!(rot * j, i, 0.000001);
     /// let rot: Matrix3f = Matrix3f::rotation_z(PI);
-    /// assert_eq_eps!(rot * j, -j, 0.00000001);
+    /// assert_eq_eps!(rot * j, -j, 0.000001);
     ///
     /// let rot: Matrix3f = Matrix3f::rotation_x(FRAC_PI_2);
-    /// assert_eq_eps!(rot * j, k, 0.00000001);
+    /// assert_eq_eps!(rot * j, k, 0.000001);
     /// let rot: Matrix3f = Matrix3f::rotation_x(-FRAC_PI_2);
-    /// assert_eq_eps!(rot * j, -k, 0.00000001);
+    /// assert_eq_eps!(rot * j, -k, 0.000001);
     /// let rot: Matrix3f = Matrix3f::rotation_x(PI);
-    /// assert_eq_eps!(rot * j, -j, 0.00000001);
+    /// assert_eq_eps!(rot * j, -j, 0.000001);
     /// let rot: Matrix3f = Matrix3f::rotation_x(FRAC_PI_2);
-    /// assert_eq_eps!(rot * k, -j, 0.00000001);
+    /// assert_eq_eps!(rot * k, -j, 0.000001);
     /// let rot: Matrix3f = Matrix3f::rotation_x(-FRAC_PI_2);
-    /// assert_eq_eps!(rot * k, j, 0.00000001);
+    /// assert_eq_eps!(rot * k, j, 0.000001);
     /// let rot: Matrix3f = Matrix3f::rotation_x(PI);
-    /// assert_eq_eps!(rot * k, -k, 0.00000001);
+    /// assert_eq_eps!(rot * k, -k, 0.000001);
     /// ```
     pub fn rotation(theta: T, a: Vec3<T>) -> Matrix3<T> {
         let cos_theta = theta.cos();
@@ -333,6 +619,80 @@ impl<T: Float + VecElem + Mul<Matrix3<T>, Output = Matrix3<T>>> Matrix3<T> {
 }
 
 impl Matrix4f {
+    /// The world->camera matrix of a camera at `eye` looking at `target`, in the camera
+    /// coordinate system with x pointing right, y down and z forward. The camera is rolled so
+    /// that `up` is as close to its up direction as possible, so `up` must not be parallel to
+    /// the viewing direction.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use math::assert_eq_eps;
+    /// use math::matrix::*;
+    /// use math::vector::*;
+    ///
+    /// let up = Vec3f::new(0.0, 0.0, 1.0);
+    /// let view = Matrix4f::look_at(Vec3f::zero(), Vec3f::new(5.0, 0.0, 0.0), up);
+    /// let camera = view.inverse().unwrap();
+    /// assert_eq!(camera.col(2).xyz(), Vec3f::new(1.0, 0.0, 0.0));
+    /// assert_eq!(camera.col(0).xyz(), Vec3f::new(0.0, -1.0, 0.0));
+    /// assert_eq!(camera.col(1).xyz(), Vec3f::new(0.0, 0.0, -1.0));
+    ///
+    /// // The target ends up straight ahead of the camera
+    /// let eye = Vec3f::new(1.0, 2.0, 3.0);
+    /// let target = Vec3f::new(-1.0, 4.0, 0.0);
+    /// let view = Matrix4f::look_at(eye, target, up);
+    /// let distance = (target - eye).length();
+    /// assert_eq_eps!(view * target.xyz1(), Vec4f::new(0.0, 0.0, distance, 1.0), 1e-5);
+    /// assert_eq_eps!(view * eye.xyz1(), Vec4f::new(0.0, 0.0, 0.0, 1.0), 1e-5);
+    /// ```
+    pub fn look_at(eye: Vec3f, target: Vec3f, up: Vec3f) -> Self {
+        let forward = (target - eye).unit();
+        let right = forward.cross(up).unit();
+        let down = forward.cross(right);
+        Self::from_rows(
+            Vec4::new(right.x(), right.y(), right.z(), -right.dot(eye)),
+            Vec4::new(down.x(), down.y(), down.z(), -down.dot(eye)),
+            Vec4::new(forward.x(), forward.y(), forward.z(), -forward.dot(eye)),
+            Vec4::new(0.0, 0.0, 0.0, 1.0),
+        )
+    }
+
+    // The vectors s, t, u and v the determinant and inverse are built from, see `inverse`
+    fn cofactor_vectors(&self) -> (Vec3f, Vec3f, Vec3f, Vec3f) {
+        let a: Vec3f = self.col(0).into();
+        let b: Vec3f = self.col(1).into();
+        let c: Vec3f = self.col(2).into();
+        let d: Vec3f = self.col(3).into();
+
+        let x = self.get(3, 0);
+        let y = self.get(3, 1);
+        let z = self.get(3, 2);
+        let w = self.get(3, 3);
+
+        (a.cross(b), c.cross(d), a * y - b * x, c * w - d * z)
+    }
+
+    /// # Examples
+    ///
+    /// ```rust
+    /// use math::matrix::*;
+    ///
+    /// assert_eq!(Matrix4f::zero().determinant(), 0.0);
+    /// assert_eq!(Matrix4f::one().determinant(), 1.0);
+    /// let m = Matrix4f::new(
+    ///     2.0, 0.0, 0.0, 1.0,
+    ///     0.0, 3.0, 0.0, 0.0,
+    ///     1.0, 0.0, 1.0, 0.0,
+    ///     0.0, 0.0, 2.0, 1.0,
+    /// );
+    /// assert_eq!(m.determinant(), 12.0);
+    /// ```
+    pub fn determinant(&self) -> f32 {
+        let (s, t, u, v) = self.cofactor_vectors();
+        s.dot(v) + t.dot(u)
+    }
+
     /// # Examples
     ///
     /// ```rust
@@ -356,10 +716,7 @@ impl Matrix4f {
         let z = self.get(3, 2);
         let w = self.get(3, 3);
 
-        let mut s = a.cross(b);
-        let mut t = c.cross(d);
-        let mut u = a * y - b * x;
-        let mut v = c * w - d * z;
+        let (mut s, mut t, mut u, mut v) = self.cofactor_vectors();
 
         let det = s.dot(v) + t.dot(u);
         if det == 0.0 {
@@ -490,6 +847,31 @@ impl<T: VecElem, const N: usize> Add for Matrix<T, N> {
     }
 }
 
+impl<T: VecElem, const N: usize> Sub for Matrix<T, N> {
+    type Output = Matrix<T, N>;
+
+    /// # Examples
+    ///
+    /// ```rust
+    /// use math::matrix::*;
+    ///
+    /// let zero = Matrix3f::zero();
+    /// let id = Matrix3f::one();
+    ///
+    /// assert_eq!(id - zero, id);
+    /// assert_eq!(id - id, zero);
+    /// assert_eq!(id + id - id, id);
+    /// assert_eq!(Matrix2i::new(1, 2, 3, 4) - Matrix2i::new(4, 3, 2, 1),
+    ///            Matrix2i::new(-3, -1, 1, 3));
+    /// ```
+    fn sub(self, rhs: Self) -> Self::Output {
+        self.columns()
+            .zip(rhs.columns())
+            .map(|(a, b)| *a - *b)
+            .collect()
+    }
+}
+
 impl<T: VecElem, const N: usize> Mul for Matrix<T, N> {
     type Output = Self;
 
@@ -577,6 +959,35 @@ impl<const N: usize> Mul<Matrix<f32, N>> for f32 {
     }
 }
 
+//
+// Formatting
+//
+
+impl<T: VecElem + fmt::Display, const N: usize> fmt::Display for Matrix<T, N> {
+    /// Formats the matrix one row per line, each row like a `Vector`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use math::matrix::*;
+    ///
+    /// assert_eq!(Matrix2i::new(1, 2, 3, 4).to_string(), "(1, 2)\n(3, 4)");
+    /// assert_eq!(
+    ///     format!("{:.1}", Matrix3f::one()),
+    ///     "(1.0, 0.0, 0.0)\n(0.0, 1.0, 0.0)\n(0.0, 0.0, 1.0)"
+    /// );
+    /// ```
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for row in 0..N {
+            if row > 0 {
+                writeln!(f)?;
+            }
+            fmt::Display::fmt(&self.row(row), f)?;
+        }
+        Ok(())
+    }
+}
+
 //
 // Iterators
 //
@@ -0,0 +1,183 @@
+use crate::matrix::Matrix3f;
+use crate::vector::Vec3f;
+use std::ops::Mul;
+
+// Past this cosine of the angle between two quaternions slerp falls back to normalized lerp,
+// as the sine in the denominator gets too close to zero
+const SLERP_LERP_THRESHOLD: f32 = 0.9995;
+
+/// A quaternion `w + v.x() i + v.y() j + v.z() k`. Unit quaternions represent rotations.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Quaternion {
+    w: f32,
+    v: Vec3f,
+}
+
+impl Quaternion {
+    pub fn new(w: f32, v: Vec3f) -> Quaternion {
+        Quaternion { w, v }
+    }
+
+    /// The quaternion of the rotation doing nothing
+    pub fn identity() -> Quaternion {
+        Quaternion::new(1.0, Vec3f::new(0.0, 0.0, 0.0))
+    }
+
+    /// The rotation by `theta` radians about the unit length `axis`, counterclockwise when
+    /// `axis` points toward the viewer like with `Matrix3::rotation`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use math::assert_eq_eps;
+    /// use math::quaternion::Quaternion;
+    /// use math::{Matrix3f, Vec3f};
+    /// use std::f32::consts::FRAC_PI_2;
+    ///
+    /// let z = Vec3f::new(0.0, 0.0, 1.0);
+    /// assert_eq_eps!(
+    ///     Quaternion::from_axis_angle(z, FRAC_PI_2).to_matrix3(),
+    ///     Matrix3f::rotation_z(FRAC_PI_2),
+    ///     1e-6
+    /// );
+    ///
+    /// let axis = Vec3f::new(1.0, -2.0, 3.0).unit();
+    /// assert_eq_eps!(
+    ///     Quaternion::from_axis_angle(axis, 0.7).to_matrix3(),
+    ///     Matrix3f::rotation(0.7, axis),
+    ///     1e-6
+    /// );
+    /// ```
+    pub fn from_axis_angle(axis: Vec3f, theta: f32) -> Quaternion {
+        let (sin_half, cos_half) = (theta * 0.5).sin_cos();
+        Quaternion::new(cos_half, axis * sin_half)
+    }
+
+    pub fn w(&self) -> f32 {
+        self.w
+    }
+
+    pub fn v(&self) -> Vec3f {
+        self.v
+    }
+
+    pub fn dot(&self, other: Quaternion) -> f32 {
+        self.w * other.w + self.v.dot(other.v)
+    }
+
+    pub fn length(&self) -> f32 {
+        self.dot(*self).sqrt()
+    }
+
+    /// Returns the quaternion scaled to length 1, which must not be of zero length.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use math::quaternion::Quaternion;
+    /// use math::Vec3f;
+    ///
+    /// let q = Quaternion::new(2.0, Vec3f::new(0.0, 2.0, 1.0)).normalize();
+    /// assert_eq!(q, Quaternion::new(2.0 / 3.0, Vec3f::new(0.0, 2.0 / 3.0, 1.0 / 3.0)));
+    /// assert_eq!(q.length(), 1.0);
+    /// ```
+    pub fn normalize(&self) -> Quaternion {
+        let length_inv = 1.0 / self.length();
+        Quaternion::new(self.w * length_inv, self.v * length_inv)
+    }
+
+    /// Spherical linear interpolation from the unit quaternion `self` at `t = 0` to `other` at
+    /// `t = 1`, rotating at constant speed along the shorter way around.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use math::assert_eq_eps;
+    /// use math::quaternion::Quaternion;
+    /// use math::Vec3f;
+    ///
+    /// let z = Vec3f::new(0.0, 0.0, 1.0);
+    /// let a = Quaternion::identity();
+    /// let b = Quaternion::from_axis_angle(z, 2.0);
+    /// let halfway = Quaternion::from_axis_angle(z, 1.0).to_matrix3();
+    ///
+    /// assert_eq_eps!(a.slerp(b, 0.0).to_matrix3(), a.to_matrix3(), 1e-6);
+    /// assert_eq_eps!(a.slerp(b, 1.0).to_matrix3(), b.to_matrix3(), 1e-6);
+    /// assert_eq_eps!(a.slerp(b, 0.5).to_matrix3(), halfway, 1e-6);
+    ///
+    /// // The same rotation as `b`, but as the negated quaternion
+    /// let minus_b = Quaternion::new(-b.w(), -b.v());
+    /// assert_eq_eps!(a.slerp(minus_b, 0.5).to_matrix3(), halfway, 1e-6);
+    /// ```
+    pub fn slerp(&self, other: Quaternion, t: f32) -> Quaternion {
+        // q and -q are the same rotation, pick the one closer to `self`
+        let mut cos = self.dot(other);
+        let other = if cos < 0.0 {
+            cos = -cos;
+            Quaternion::new(-other.w, -other.v)
+        } else {
+            other
+        };
+
+        let (a, b) = if cos > SLERP_LERP_THRESHOLD {
+            (1.0 - t, t)
+        } else {
+            let angle = cos.acos();
+            let sin_inv = 1.0 / angle.sin();
+            (
+                ((1.0 - t) * angle).sin() * sin_inv,
+                (t * angle).sin() * sin_inv,
+            )
+        };
+        Quaternion::new(a * self.w + b * other.w, self.v * a + other.v * b).normalize()
+    }
+
+    /// The rotation matrix of the unit quaternion
+    pub fn to_matrix3(&self) -> Matrix3f {
+        let w = self.w;
+        let (x, y, z) = (self.v.x(), self.v.y(), self.v.z());
+
+        Matrix3f::new(
+            1.0 - 2.0 * (y * y + z * z),
+            2.0 * (x * y - w * z),
+            2.0 * (x * z + w * y),
+            2.0 * (x * y + w * z),
+            1.0 - 2.0 * (x * x + z * z),
+            2.0 * (y * z - w * x),
+            2.0 * (x * z - w * y),
+            2.0 * (y * z + w * x),
+            1.0 - 2.0 * (x * x + y * y),
+        )
+    }
+}
+
+impl Mul for Quaternion {
+    type Output = Quaternion;
+
+    /// The Hamilton product. For unit quaternions `a * b` is the rotation `b` followed by `a`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use math::assert_eq_eps;
+    /// use math::quaternion::Quaternion;
+    /// use math::Vec3f;
+    ///
+    /// let i = Quaternion::new(0.0, Vec3f::new(1.0, 0.0, 0.0));
+    /// let j = Quaternion::new(0.0, Vec3f::new(0.0, 1.0, 0.0));
+    /// let k = Quaternion::new(0.0, Vec3f::new(0.0, 0.0, 1.0));
+    /// assert_eq!(i * j, k);
+    /// assert_eq!(j * i, Quaternion::new(0.0, Vec3f::new(0.0, 0.0, -1.0)));
+    /// assert_eq!(i * i, Quaternion::new(-1.0, Vec3f::new(0.0, 0.0, 0.0)));
+    ///
+    /// let a = Quaternion::from_axis_angle(Vec3f::new(1.0, 0.0, 0.0), 0.3);
+    /// let b = Quaternion::from_axis_angle(Vec3f::new(0.0, 1.0, 0.0), 1.1);
+    /// assert_eq_eps!((a * b).to_matrix3(), a.to_matrix3() * b.to_matrix3(), 1e-6);
+    /// ```
+    fn mul(self, rhs: Self) -> Self::Output {
+        Quaternion::new(
+            self.w * rhs.w - self.v.dot(rhs.v),
+            rhs.v * self.w + self.v * rhs.w + self.v.cross(rhs.v),
+        )
+    }
+}
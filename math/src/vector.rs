@@ -1,5 +1,6 @@
 use crate::matrix::Matrix3;
 pub use num::{Float, Num, Zero};
+use std::fmt;
 use std::iter::FromIterator;
 use std::ops::{Add, AddAssign, Div, Index, IndexMut, Mul, Neg, Sub, SubAssign};
 
@@ -142,6 +143,29 @@ impl<T: VecElem> Vec2<T> {
     }
 }
 
+impl<T: VecElem> Vec2<T> {
+    /// Returns the perp-dot product, i.e. the z component of the cross product of `self` and
+    /// `other` extended to 3D. It is positive if `other` turns counterclockwise from `self`,
+    /// negative if clockwise and 0 if they are collinear.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use math::vector::*;
+    ///
+    /// let i = Vec2i::new(1, 0);
+    /// let j = Vec2i::new(0, 1);
+    ///
+    /// assert_eq!(i.perp_dot(j), 1);
+    /// assert_eq!(j.perp_dot(i), -1);
+    /// assert_eq!(Vec2i::new(2, 3).perp_dot(Vec2i::new(-4, -6)), 0);
+    /// assert_eq!(Vec2f::new(2.0, 1.0).perp_dot(Vec2f::new(1.0, 3.0)), 5.0);
+    /// ```
+    pub fn perp_dot(&self, other: Vec2<T>) -> T {
+        self.x() * other.y() - self.y() * other.x()
+    }
+}
+
 impl<T: VecElem> Vec3<T> {
     pub fn new(x: T, y: T, z: T) -> Vec3<T> {
         Vec3 {
@@ -181,6 +205,21 @@ impl<T: VecElem, const N: usize> Vector<T, N> {
     pub fn iter_mut(&mut self) -> VectorMutIterator<T> {
         self.into_iter()
     }
+
+    /// # Examples
+    ///
+    /// ```rust
+    /// use math::vector::*;
+    ///
+    /// let v = Vec4f::from([1.0, 2.0, 3.0, 4.0]);
+    /// assert_eq!(v.to_array(), [1.0, 2.0, 3.0, 4.0]);
+    ///
+    /// let a: [i32; 3] = Vec3i::new(1, 2, 3).into();
+    /// assert_eq!(a, [1, 2, 3]);
+    /// ```
+    pub fn to_array(&self) -> [T; N] {
+        self.components
+    }
 }
 
 impl<T: VecElem, const N: usize> Vector<T, N> {
@@ -208,6 +247,34 @@ impl<T: VecElem, const N: usize> Vector<T, N> {
         sum
     }
 
+    /// Returns the sum of the components.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use math::vector::*;
+    ///
+    /// assert_eq!(Vec4i::new(1, 2, 3, 4).sum(), 10);
+    /// assert_eq!(Vec3f::new(0.5, -1.0, 2.0).sum(), 1.5);
+    /// ```
+    pub fn sum(&self) -> T {
+        self.iter().fold(T::zero(), |acc, c| acc + c)
+    }
+
+    /// Returns the product of the components.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use math::vector::*;
+    ///
+    /// assert_eq!(Vec4i::new(1, 2, 3, 4).product(), 24);
+    /// assert_eq!(Vec3f::new(0.5, -1.0, 2.0).product(), -1.0);
+    /// ```
+    pub fn product(&self) -> T {
+        self.iter().fold(T::one(), |acc, c| acc * c)
+    }
+
     pub fn length_squared(&self) -> T {
         self.dot(*self)
     }
@@ -216,6 +283,23 @@ impl<T: VecElem, const N: usize> Vector<T, N> {
         self.length_squared().as_f32().sqrt()
     }
 
+    /// Returns the vector with `f` applied to each component.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use math::vector::*;
+    ///
+    /// let v = Vec3f::new(1.5, -0.5, 2.0);
+    /// assert_eq!(v.map(|x| x.floor()), Vec3f::new(1.0, -1.0, 2.0));
+    ///
+    /// let v = Vec3i::new(1, 2, 3);
+    /// assert_eq!(v.map(|x| x as f32), Vec3f::new(1.0, 2.0, 3.0));
+    /// ```
+    pub fn map<U: VecElem, F: Fn(T) -> U>(&self, f: F) -> Vector<U, N> {
+        self.iter().map(f).collect()
+    }
+
     pub fn as_f32(&self) -> Vector<f32, N> {
         let mut x = Vector::<f32, N>::zero();
         for i in 0..N {
@@ -224,6 +308,8 @@ impl<T: VecElem, const N: usize> Vector<T, N> {
         x
     }
 
+    /// Returns the vector scaled to length 1. The vector must not be of zero length, or the
+    /// components will be NaN; use `try_unit` when that is possible.
     pub fn unit(&self) -> Vector<f32, N> {
         let length_inv = 1.0 / self.length();
         let mut x = Vector::<f32, N>::zero();
@@ -232,6 +318,151 @@ impl<T: VecElem, const N: usize> Vector<T, N> {
         }
         x
     }
+
+    /// Returns the vector scaled to length 1, or `None` if it is (close to) the zero vector.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use math::vector::*;
+    ///
+    /// assert_eq!(Vec3f::zero().try_unit(), None);
+    /// assert_eq!(Vec3f::new(1e-9, 0.0, 0.0).try_unit(), None);
+    /// assert_eq!(Vec3i::new(0, 3, 0).try_unit(), Some(Vec3f::new(0.0, 1.0, 0.0)));
+    /// ```
+    pub fn try_unit(&self) -> Option<Vector<f32, N>> {
+        if self.length() < f32::EPSILON {
+            None
+        } else {
+            Some(self.unit())
+        }
+    }
+
+    /// Linearly interpolate from `self` at `t = 0` to `other` at `t = 1`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use math::vector::*;
+    ///
+    /// let a = Vec3f::new(1.0, 2.0, 3.0);
+    /// let b = Vec3f::new(3.0, 6.0, -1.0);
+    /// assert_eq!(a.lerp(b, 0.0), a);
+    /// assert_eq!(a.lerp(b, 1.0), b);
+    /// assert_eq!(a.lerp(b, 0.5), Vec3f::new(2.0, 4.0, 1.0));
+    ///
+    /// let a = Vec3i::new(0, 2, 4);
+    /// let b = Vec3i::new(1, 4, 0);
+    /// assert_eq!(a.lerp(b, 0.0), Vec3f::new(0.0, 2.0, 4.0));
+    /// assert_eq!(a.lerp(b, 1.0), Vec3f::new(1.0, 4.0, 0.0));
+    /// assert_eq!(a.lerp(b, 0.5), Vec3f::new(0.5, 3.0, 2.0));
+    /// ```
+    pub fn lerp(&self, other: Self, t: f32) -> Vector<f32, N> {
+        self.as_f32() * (1.0 - t) + other.as_f32() * t
+    }
+
+    /// Returns the Euclidean distance between the points at `self` and `other`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use math::vector::*;
+    ///
+    /// assert_eq!(Vec2f::new(1.0, 1.0).distance(Vec2f::new(4.0, 5.0)), 5.0);
+    /// assert_eq!(Vec3i::new(1, 2, 3).distance(Vec3i::new(1, 2, 3)), 0.0);
+    /// ```
+    pub fn distance(&self, other: Self) -> f32 {
+        (*self - other).length()
+    }
+}
+
+impl<T: VecElem + PartialOrd, const N: usize> Vector<T, N> {
+    /// Returns the component-wise minimum of `self` and `other`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use math::vector::*;
+    ///
+    /// let a = Vec3f::new(1.0, 5.0, -2.0);
+    /// let b = Vec3f::new(3.0, 4.0, -2.5);
+    /// assert_eq!(a.min(b), Vec3f::new(1.0, 4.0, -2.5));
+    /// ```
+    pub fn min(&self, other: Self) -> Self {
+        self.iter()
+            .zip(other.iter())
+            .map(|(a, b)| if b < a { b } else { a })
+            .collect()
+    }
+
+    /// Returns the component-wise maximum of `self` and `other`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use math::vector::*;
+    ///
+    /// let a = Vec3f::new(1.0, 5.0, -2.0);
+    /// let b = Vec3f::new(3.0, 4.0, -2.5);
+    /// assert_eq!(a.max(b), Vec3f::new(3.0, 5.0, -2.0));
+    /// ```
+    pub fn max(&self, other: Self) -> Self {
+        self.iter()
+            .zip(other.iter())
+            .map(|(a, b)| if b > a { b } else { a })
+            .collect()
+    }
+
+    /// Clamp each component between the corresponding components of `lo` and `hi`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use math::vector::*;
+    ///
+    /// let v = Vec3f::new(-1.0, 0.5, 2.0);
+    /// let clamped = v.clamp(Vec3f::zero(), Vec3f::new(1.0, 1.0, 1.0));
+    /// assert_eq!(clamped, Vec3f::new(0.0, 0.5, 1.0));
+    /// ```
+    pub fn clamp(&self, lo: Self, hi: Self) -> Self {
+        self.max(lo).min(hi)
+    }
+}
+
+impl<const N: usize> Vector<f32, N> {
+    /// Scale the vector in place to length 1. Like with `unit`, the vector must not be of zero
+    /// length.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use math::vector::*;
+    ///
+    /// let mut v = Vec2f::new(3.0, -4.0);
+    /// v.normalize_mut();
+    /// assert_eq!(v, Vec2f::new(0.6, -0.8));
+    /// ```
+    pub fn normalize_mut(&mut self) {
+        *self = self.unit();
+    }
+
+    /// Whether each component of `self` is less than `eps` away from the corresponding one in
+    /// `other`, the same comparison `assert_eq_eps!` makes.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use math::vector::*;
+    ///
+    /// let v = Vec3f::new(1.0, 2.0, 3.0);
+    /// assert!(v.approx_eq(Vec3f::new(1.0, 2.0000001, 2.9999999), 1e-6));
+    /// assert!(!v.approx_eq(Vec3f::new(1.0, 2.1, 3.0), 1e-6));
+    /// ```
+    pub fn approx_eq(&self, other: Self, eps: f32) -> bool {
+        self.iter()
+            .zip(other.iter())
+            .all(|(a, b)| (a - b).abs() < eps)
+    }
 }
 
 impl<T: VecElem> Vec3<T> {
@@ -305,6 +536,183 @@ impl<T: VecElem> Vec3<T> {
     }
 }
 
+impl Vec3f {
+    /// Returns the vector with spherical coordinates (`radius`, `theta`, `phi`), where `theta`
+    /// is the polar angle measured from the positive z axis (world up, camera forward) and `phi`
+    /// is the azimuthal angle in the xy plane, measured from positive x towards positive y
+    /// (camera down).
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use math::assert_eq_eps;
+    /// use math::vector::*;
+    /// use std::f32::consts::{FRAC_PI_2, PI};
+    ///
+    /// assert_eq_eps!(Vec3f::from_spherical(2.0, 0.0, 0.0), Vec3f::new(0.0, 0.0, 2.0), 1e-6);
+    /// assert_eq_eps!(Vec3f::from_spherical(1.0, FRAC_PI_2, 0.0), Vec3f::new(1.0, 0.0, 0.0), 1e-6);
+    /// assert_eq_eps!(Vec3f::from_spherical(1.0, FRAC_PI_2, FRAC_PI_2), Vec3f::new(0.0, 1.0, 0.0), 1e-6);
+    /// assert_eq_eps!(Vec3f::from_spherical(1.0, PI, 0.0), Vec3f::new(0.0, 0.0, -1.0), 1e-6);
+    /// ```
+    pub fn from_spherical(radius: f32, theta: f32, phi: f32) -> Vec3f {
+        let (sin_theta, cos_theta) = theta.sin_cos();
+        let (sin_phi, cos_phi) = phi.sin_cos();
+        Vec3f::new(
+            radius * sin_theta * cos_phi,
+            radius * sin_theta * sin_phi,
+            radius * cos_theta,
+        )
+    }
+
+    /// Returns the spherical coordinates (`radius`, `theta`, `phi`) of the vector, see
+    /// `from_spherical` for the convention. `theta` is in [0, π] and `phi` in (-π, π]. For
+    /// vectors on the z axis `phi` is 0, and for the zero vector all coordinates are 0.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use math::assert_eq_eps;
+    /// use math::vector::*;
+    /// use std::f32::consts::{FRAC_PI_2, PI};
+    ///
+    /// for v in &[Vec3f::new(1.0, 2.0, 3.0), Vec3f::new(-1.0, 0.5, -2.0), Vec3f::new(0.0, -3.0, 0.0)] {
+    ///     let (r, theta, phi) = v.to_spherical();
+    ///     assert_eq_eps!(Vec3f::from_spherical(r, theta, phi), *v, 1e-6);
+    /// }
+    ///
+    /// assert_eq!(Vec3f::new(0.0, 0.0, 2.0).to_spherical(), (2.0, 0.0, 0.0));
+    /// assert_eq!(Vec3f::new(0.0, 0.0, -2.0).to_spherical(), (2.0, PI, 0.0));
+    /// assert_eq!(Vec3f::zero().to_spherical(), (0.0, 0.0, 0.0));
+    /// assert_eq!(Vec3f::new(0.0, 1.0, 0.0).to_spherical(), (1.0, FRAC_PI_2, FRAC_PI_2));
+    /// ```
+    pub fn to_spherical(&self) -> (f32, f32, f32) {
+        let radius = self.length();
+        if radius == 0.0 {
+            return (0.0, 0.0, 0.0);
+        }
+        let theta = (self.z() / radius).clamp(-1.0, 1.0).acos();
+        let phi = self.y().atan2(self.x());
+        (radius, theta, phi)
+    }
+
+    /// Returns the angle between the vector and `other` in radians, in [0, π]. Neither may be
+    /// the zero vector.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use math::assert_eq_eps;
+    /// use math::vector::*;
+    /// use std::f32::consts::{FRAC_PI_2, FRAC_PI_4, PI};
+    ///
+    /// let x = Vec3f::new(1.0, 0.0, 0.0);
+    /// let y = Vec3f::new(0.0, 1.0, 0.0);
+    /// assert_eq_eps!(x.angle_between(y), FRAC_PI_2, 1e-6);
+    /// assert_eq_eps!(x.angle_between(Vec3f::new(1.0, 1.0, 0.0)), FRAC_PI_4, 1e-6);
+    /// assert_eq_eps!(x.angle_between(-2.0 * x), PI, 1e-6);
+    ///
+    /// let v = Vec3f::new(1.0, 2.0, 2.0);
+    /// assert_eq!(v.angle_between(v), 0.0);
+    /// assert_eq!(v.angle_between(3.0 * v), 0.0);
+    /// ```
+    pub fn angle_between(&self, other: Vec3f) -> f32 {
+        let cos = self.dot(other) / (self.length() * other.length());
+        cos.clamp(-1.0, 1.0).acos()
+    }
+
+    /// Rotate the vector toward the direction of `target` by at most `max_radians`, keeping its
+    /// length. If the angle between them is at most `max_radians`, the result points along
+    /// `target`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use math::assert_eq_eps;
+    /// use math::vector::*;
+    /// use std::f32::consts::{FRAC_1_SQRT_2, FRAC_PI_2, FRAC_PI_4, PI};
+    ///
+    /// let x = Vec3f::new(1.0, 0.0, 0.0);
+    /// let y = Vec3f::new(0.0, 1.0, 0.0);
+    /// assert_eq_eps!(x.rotate_toward(y, FRAC_PI_4), Vec3f::new(FRAC_1_SQRT_2, FRAC_1_SQRT_2, 0.0), 1e-6);
+    /// assert_eq_eps!(x.rotate_toward(3.0 * y, PI), y, 1e-6);
+    ///
+    /// // Any direction is as good as another for turning around
+    /// let turned = (2.0 * x).rotate_toward(-x, FRAC_PI_2);
+    /// assert_eq_eps!(turned.length(), 2.0, 1e-6);
+    /// assert_eq_eps!(turned.dot(x), 0.0, 1e-6);
+    /// ```
+    pub fn rotate_toward(&self, target: Vec3f, max_radians: f32) -> Vec3f {
+        let axis = self.cross(target);
+        let angle = axis.length().atan2(self.dot(target));
+        if angle <= max_radians {
+            return target.unit() * self.length();
+        }
+
+        // Opposite directions, any perpendicular axis will do
+        let axis = if axis.length_squared() > 0.0 {
+            axis
+        } else if self.x().abs() < self.y().abs() {
+            self.cross(Vec3f::new(1.0, 0.0, 0.0))
+        } else {
+            self.cross(Vec3f::new(0.0, 1.0, 0.0))
+        };
+        Matrix3::rotation(max_radians, axis.unit()) * *self
+    }
+
+    /// Reflect the vector across the plane with the unit length `normal`, e.g. to get the
+    /// direction of a bounced ray.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use math::vector::*;
+    ///
+    /// let up = Vec3f::new(0.0, 1.0, 0.0);
+    /// assert_eq!(Vec3f::new(1.0, -1.0, 0.0).reflect(up), Vec3f::new(1.0, 1.0, 0.0));
+    /// assert_eq!(Vec3f::new(1.0, 0.0, 2.0).reflect(up), Vec3f::new(1.0, 0.0, 2.0));
+    /// ```
+    pub fn reflect(&self, normal: Vec3f) -> Vec3f {
+        *self - 2.0 * self.dot(normal) * normal
+    }
+
+    /// Returns the component of the vector in the direction of `other`, which must not be the
+    /// zero vector.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use math::vector::*;
+    ///
+    /// let v = Vec3f::new(2.0, 2.0, 0.0);
+    /// assert_eq!(v.project_onto(Vec3f::new(1.0, 0.0, 0.0)), Vec3f::new(2.0, 0.0, 0.0));
+    /// assert_eq!(v.project_onto(Vec3f::new(0.0, -3.0, 0.0)), Vec3f::new(0.0, 2.0, 0.0));
+    /// assert_eq!(v.project_onto(Vec3f::new(0.0, 0.0, 1.0)), Vec3f::zero());
+    /// ```
+    pub fn project_onto(&self, other: Vec3f) -> Vec3f {
+        other * (self.dot(other) / other.length_squared())
+    }
+
+    /// Move the point at the vector toward `target` by at most `max_dist`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use math::vector::*;
+    ///
+    /// let v = Vec3f::new(1.0, 1.0, 1.0);
+    /// assert_eq!(v.move_toward(Vec3f::new(1.0, 11.0, 1.0), 2.0), Vec3f::new(1.0, 3.0, 1.0));
+    /// assert_eq!(v.move_toward(Vec3f::new(1.0, 2.0, 1.0), 2.0), Vec3f::new(1.0, 2.0, 1.0));
+    /// ```
+    pub fn move_toward(&self, target: Vec3f, max_dist: f32) -> Vec3f {
+        let delta = target - *self;
+        if delta.length() <= max_dist {
+            target
+        } else {
+            *self + delta.unit() * max_dist
+        }
+    }
+}
+
 //
 // Arithmetic
 //
@@ -336,7 +744,20 @@ impl<T: VecElem, const N: usize> Add for Vector<T, N> {
     }
 }
 
-impl<T: VecElem> AddAssign for Vec3<T> {
+impl<T: VecElem, const N: usize> AddAssign for Vector<T, N> {
+    /// # Examples
+    ///
+    /// ```rust
+    /// use math::vector::*;
+    ///
+    /// let mut v = Vec4f::new(1.0, 2.0, 3.0, 4.0);
+    /// v += Vec4f::new(0.5, 0.5, 0.5, 0.5);
+    /// assert_eq!(v, Vec4f::new(1.5, 2.5, 3.5, 4.5));
+    ///
+    /// let mut v = Vec2f::new(1.0, 2.0);
+    /// v += Vec2f::new(-1.0, 1.0);
+    /// assert_eq!(v, Vec2f::new(0.0, 3.0));
+    /// ```
     fn add_assign(&mut self, rhs: Self) {
         for (i, c) in self.iter_mut().enumerate() {
             *c = *c + rhs[i];
@@ -368,6 +789,19 @@ impl<T: VecElem, const N: usize> Sub for Vector<T, N> {
 }
 
 impl<T: VecElem, const N: usize> SubAssign for Vector<T, N> {
+    /// # Examples
+    ///
+    /// ```rust
+    /// use math::vector::*;
+    ///
+    /// let mut v = Vec4f::new(1.0, 2.0, 3.0, 4.0);
+    /// v -= Vec4f::new(1.0, 1.0, 1.0, 1.0);
+    /// assert_eq!(v, Vec4f::new(0.0, 1.0, 2.0, 3.0));
+    ///
+    /// let mut v = Vec2f::new(1.0, 2.0);
+    /// v -= Vec2f::new(-1.0, 1.0);
+    /// assert_eq!(v, Vec2f::new(2.0, 1.0));
+    /// ```
     fn sub_assign(&mut self, rhs: Self) {
         for (i, c) in self.iter_mut().enumerate() {
             *c = *c - rhs[i];
@@ -573,6 +1007,60 @@ impl<T: VecElem, const N: usize> IndexMut<usize> for Vector<T, N> {
     }
 }
 
+//
+// Formatting
+//
+
+impl<T: VecElem + fmt::Display, const N: usize> fmt::Display for Vector<T, N> {
+    /// Formats the components in parentheses, passing on the precision etc. to each of them.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use math::vector::*;
+    ///
+    /// assert_eq!(Vec3i::new(1, 2, 3).to_string(), "(1, 2, 3)");
+    /// assert_eq!(format!("{:.2}", Vec2f::new(0.5, -1.0)), "(0.50, -1.00)");
+    /// ```
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "(")?;
+        for (i, c) in self.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            fmt::Display::fmt(&c, f)?;
+        }
+        write!(f, ")")
+    }
+}
+
+//
+// Conversions
+//
+
+impl<T: VecElem, const N: usize> From<[T; N]> for Vector<T, N> {
+    /// # Examples
+    ///
+    /// ```rust
+    /// use math::vector::*;
+    ///
+    /// let v = Vec4f::from([1.0, 2.0, 3.0, 4.0]);
+    /// assert_eq!(v, Vec4f::new(1.0, 2.0, 3.0, 4.0));
+    ///
+    /// let v: Vec2i = [5, 6].into();
+    /// assert_eq!(v, Vec2i::new(5, 6));
+    /// ```
+    fn from(components: [T; N]) -> Self {
+        Self { components }
+    }
+}
+
+impl<T: VecElem, const N: usize> From<Vector<T, N>> for [T; N] {
+    fn from(v: Vector<T, N>) -> Self {
+        v.components
+    }
+}
+
 impl<T: VecElem> From<Vec3<T>> for Vec4<T> {
     fn from(v: Vec3<T>) -> Self {
         Self::new(v.x(), v.y(), v.z(), T::zero())